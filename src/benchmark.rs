@@ -1,32 +1,291 @@
 use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::mem;
 use std::str::FromStr;
 
-use prettytable::Row;
 use regex::Regex;
 
+use error::{Error, ParseError, Result};
+
 /// Two sets of benchmarks that are comparable but haven't been paired up yet.
 #[derive(Clone, Debug)]
 pub struct Benchmarks {
     old: Vec<Benchmark>,
     new: Vec<Benchmark>,
+    failed_old: Vec<Failure>,
+    failed_new: Vec<Failure>,
+    metric: Option<String>,
 }
 
+/// The metrics benchmarks are compared by unless another one is selected,
+/// in order of preference. Instruction-count benchmarks have no time, so
+/// they are compared by their estimated cycles or instructions instead.
+const DEFAULT_METRICS: [&str; 3] = ["time", "estimated_cycles", "instructions"];
+
 impl Benchmarks {
     /// Create a new empty set of comparable benchmarks.
     pub fn from(pair: (Vec<Benchmark>, Vec<Benchmark>)) -> Benchmarks {
         Benchmarks {
             old: pair.0,
             new: pair.1,
+            failed_old: vec![],
+            failed_new: vec![],
+            metric: None,
         }
     }
 
+    /// Adds benchmarks that didn't produce a result to both sets.
+    ///
+    /// When paired, a failure is reported instead of the benchmark being
+    /// missing from its set. Ignored benchmarks are only reported if the
+    /// other set has a result for them, since libtest also lists every
+    /// ordinary test as ignored when benchmarking.
+    pub fn add_failures(&mut self, old: Vec<Failure>, new: Vec<Failure>) {
+        self.failed_old.extend(old);
+        self.failed_new.extend(new);
+    }
+
     /// Create a set of pairwise comparisons between benchmarks.
     ///
     /// The old and new benchmarks are paired based on whether they have
-    /// equivalent names. Benchmarks without a pair are marked as unpaired.
+    /// equivalent names, and compared by the metric returned by `metric`.
+    /// Benchmarks without a pair, or without that metric, are marked as
+    /// unpaired.
     pub fn paired(self) -> PairedBenchmarks {
         PairedBenchmarks::from(self)
     }
+
+    /// Normalize both sets of benchmarks against a reference benchmark.
+    ///
+    /// Each set is divided by its own time of the benchmark named `name`,
+    /// which cancels out differences in machine speed between the two runs.
+    /// The results are then scaled back up by the old reference time, so
+    /// that the old set is unchanged and the new set is expressed in "old
+    /// machine" nanoseconds. This applies to every metric measured in time,
+    /// and inversely to rates like throughput. Counts are left as they are.
+    ///
+    /// An error is returned if either set lacks the reference benchmark or
+    /// its time, or if it took 0 ns/iter.
+    pub fn normalize_by(&mut self, name: &str) -> Result<()> {
+        let ref_old = reference_time(&self.old, name, "old")?;
+        let ref_new = reference_time(&self.new, name, "new")?;
+        for bench in &mut self.new {
+            bench.scale(ref_new, ref_old);
+        }
+        Ok(())
+    }
+
+    /// Prefix the name of every benchmark with its bench target or crate.
+    ///
+    /// Since benchmarks are paired and sorted by name, this groups them by
+    /// target or crate. Names of benchmarks whose target or crate isn't
+    /// known are left as they are.
+    pub fn qualify(&mut self, by: Qualifier) {
+        for bench in self.old.iter_mut().chain(&mut self.new) {
+            by.qualify(&mut bench.name, &bench.target, &bench.krate);
+        }
+        for failure in self.failed_old.iter_mut().chain(&mut self.failed_new) {
+            by.qualify(&mut failure.name, &failure.target, &failure.krate);
+        }
+    }
+
+    /// Keep only the benchmarks and failures from the given crates.
+    ///
+    /// Those whose crate isn't known are dropped.
+    pub fn retain_crates(&mut self, crates: &[String]) {
+        let keep = |krate: &Option<String>| krate.as_ref().is_some_and(|k| crates.contains(k));
+        self.old.retain(|b| keep(&b.krate));
+        self.new.retain(|b| keep(&b.krate));
+        self.failed_old.retain(|f| keep(&f.krate));
+        self.failed_new.retain(|f| keep(&f.krate));
+    }
+
+    /// Make the names of the benchmarks in each set unique.
+    ///
+    /// Without this, benchmarks that share a name within a set are paired
+    /// arbitrarily. An error is returned if a set has duplicates and the
+    /// policy is `Duplicates::Error`, or if prefixing them with their bench
+    /// target and crate doesn't make them unique.
+    pub fn dedup(&mut self, policy: Duplicates) -> Result<()> {
+        if policy == Duplicates::Target {
+            for &qualifier in &[Qualifier::Target, Qualifier::Crate] {
+                let mut names = duplicated_names(&self.old);
+                names.extend(duplicated_names(&self.new));
+                for bench in self.old.iter_mut().chain(&mut self.new) {
+                    if names.contains(&bench.name) {
+                        qualifier.qualify(&mut bench.name, &bench.target, &bench.krate);
+                    }
+                }
+            }
+        }
+        let metric = self.metric();
+        self.old = dedup_set(mem::take(&mut self.old), policy, &metric, "old")?;
+        self.new = dedup_set(mem::take(&mut self.new), policy, &metric, "new")?;
+        Ok(())
+    }
+
+    /// Returns an `Error::DuplicateBenchmark` for every name that occurs more
+    /// than once in either set, e.g., to warn about them before `dedup`.
+    pub fn duplicates(&self) -> Vec<Error> {
+        let sets = [(&self.old, "old"), (&self.new, "new")];
+        sets.iter()
+            .flat_map(|&(benches, set)| {
+                duplicated_names(benches).into_iter().map(move |name| {
+                    let count = benches.iter().filter(|b| b.name == name).count();
+                    Error::DuplicateBenchmark { name, count, set }
+                })
+            })
+            .collect()
+    }
+
+    /// Compare the benchmarks by their metric named `name`, e.g.,
+    /// `instructions` or `throughput`, instead of by the default metric.
+    ///
+    /// Comparisons then take into account whether lower or higher values
+    /// of that metric are better. An error is returned if any benchmark
+    /// lacks the metric.
+    pub fn select_metric(&mut self, name: &str) -> Result<()> {
+        if let Some(bench) = self.old.iter().chain(&self.new).find(|b| b.metric(name).is_none()) {
+            return Err(Error::MissingMetric {
+                name: name.to_string(),
+                benchmark: bench.name.clone(),
+            });
+        }
+        self.metric = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Returns the name of the metric the benchmarks are compared by.
+    ///
+    /// Unless one was selected with `select_metric`, this is the first of
+    /// `time`, `estimated_cycles` and `instructions` that every benchmark
+    /// has, or `time` if none of them is.
+    pub fn metric(&self) -> String {
+        if let Some(ref metric) = self.metric {
+            return metric.clone();
+        }
+        let all_have = |name: &str| self.old.iter().chain(&self.new).all(|b| b.metric(name).is_some());
+        DEFAULT_METRICS.iter().find(|&&name| all_have(name)).unwrap_or(&"time").to_string()
+    }
+}
+
+/// What to do with benchmarks that share a name within one set, e.g.,
+/// because the output of several runs was concatenated, or because
+/// benchmarks in different bench targets have the same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Duplicates {
+    /// Fail with `Error::DuplicateBenchmark`.
+    Error,
+    /// Keep the first result.
+    First,
+    /// Keep the last result.
+    Last,
+    /// Keep the best result of the compared metric, e.g., the fastest.
+    Min,
+    /// Keep the median result of the compared metric, or the better of the
+    /// two middle results.
+    Median,
+    /// Prefix each duplicated name with the bench target of the benchmark,
+    /// e.g., `foo::bench`, in both sets. Names that are still duplicated,
+    /// because targets of different crates share a name, are prefixed with
+    /// the crate as well, e.g., `my_crate::foo::bench`. Fail if that doesn't
+    /// make the names unique.
+    Target,
+}
+
+/// What to prefix the names of benchmarks with, so that benchmarks with the
+/// same name in different bench targets or crates can be told apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Qualifier {
+    /// The bench target, e.g., `foo::bench`.
+    Target,
+    /// The crate, e.g., `my_crate::bench`.
+    Crate,
+}
+
+impl Qualifier {
+    /// Prefixes `name` with the bench target or crate, if it is known.
+    fn qualify(self, name: &mut String, target: &Option<String>, krate: &Option<String>) {
+        let prefix = match self {
+            Qualifier::Target => target,
+            Qualifier::Crate => krate,
+        };
+        if let Some(prefix) = prefix.as_ref() {
+            *name = format!("{}::{}", prefix, name);
+        }
+    }
+}
+
+/// Returns the names that occur more than once in `benches`.
+fn duplicated_names(benches: &[Benchmark]) -> BTreeSet<String> {
+    let mut seen = BTreeSet::new();
+    benches.iter().filter(|b| !seen.insert(&b.name)).map(|b| b.name.clone()).collect()
+}
+
+/// Reduces every group of benchmarks with the same name in `benches` to
+/// one benchmark, keeping the order in which names first occur.
+fn dedup_set(benches: Vec<Benchmark>,
+             policy: Duplicates,
+             metric: &str,
+             set: &'static str)
+             -> Result<Vec<Benchmark>> {
+    // Orders benchmarks from best to worst, with those lacking the metric
+    // last.
+    let rank = |b: &Benchmark| match b.metric(metric) {
+        None => f64::INFINITY,
+        Some(m) if m.direction == Direction::HigherIsBetter => -m.value,
+        Some(m) => m.value,
+    };
+    let mut groups: Vec<Vec<Benchmark>> = vec![];
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for bench in benches {
+        match index.get(&bench.name) {
+            Some(&i) => groups[i].push(bench),
+            None => {
+                index.insert(bench.name.clone(), groups.len());
+                groups.push(vec![bench]);
+            }
+        }
+    }
+    groups.into_iter()
+        .map(|mut group| {
+            if group.len() == 1 {
+                return Ok(group.remove(0));
+            }
+            match policy {
+                Duplicates::Error | Duplicates::Target => {
+                    Err(Error::DuplicateBenchmark {
+                        name: group.swap_remove(0).name,
+                        count: group.len() + 1,
+                        set,
+                    })
+                }
+                Duplicates::First => Ok(group.swap_remove(0)),
+                Duplicates::Last => Ok(group.pop().unwrap()),
+                Duplicates::Min => Ok(group.into_iter().min_by(|a, b| rank(a).total_cmp(&rank(b))).unwrap()),
+                Duplicates::Median => {
+                    group.sort_by(|a, b| rank(a).total_cmp(&rank(b)));
+                    Ok(group.swap_remove((group.len() - 1) / 2))
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the time of the benchmark named `name` in `benches`, which must
+/// exist and be non-zero to be usable as a reference.
+fn reference_time(benches: &[Benchmark], name: &str, set: &'static str) -> Result<f64> {
+    let bench = match benches.iter().find(|b| b.name == name) {
+        None => return Err(Error::MissingReference { name: name.to_string(), set }),
+        Some(bench) => bench,
+    };
+    match bench.metric("time") {
+        None => Err(Error::MissingMetric { name: "time".to_string(), benchmark: bench.name.clone() }),
+        Some(time) if time.value == 0.0 => Err(Error::ZeroReference { name: name.to_string(), set }),
+        Some(time) => Ok(time.value),
+    }
 }
 
 /// `PairedBenchmarks` is a set of paired benchmarks.
@@ -34,26 +293,76 @@ impl Benchmarks {
 /// This also provides access to unpaired benchmarks.
 #[derive(Clone, Debug)]
 pub struct PairedBenchmarks {
+    metric: String,
     cmps: Vec<Comparison>,
     unpaired_old: Vec<Benchmark>,
     unpaired_new: Vec<Benchmark>,
+    missing_metric: Vec<String>,
+    failed_old: Vec<Failure>,
+    failed_new: Vec<Failure>,
 }
 
 impl From<Benchmarks> for PairedBenchmarks {
     fn from(mut benches: Benchmarks) -> PairedBenchmarks {
+        let metric = benches.metric();
         benches.old.sort();
         benches.new.sort();
-        let ov = Overlap::find(benches.old, benches.new, Benchmark::cmp);
-        let cmps = ov.overlap.into_iter().map(|(a, b)| a.compare(b)).collect();
+        let failed_old = relevant_failures(benches.failed_old, &benches.new);
+        let failed_new = relevant_failures(benches.failed_new, &benches.old);
+        let Overlap { left: mut unpaired_old, overlap, right: mut unpaired_new } =
+            Overlap::find(benches.old, benches.new, Benchmark::cmp);
+        let (mut cmps, mut missing_metric) = (vec![], vec![]);
+        for (a, b) in overlap {
+            if a.metric(&metric).is_some() && b.metric(&metric).is_some() {
+                cmps.push(a.compare(b, &metric).unwrap());
+            } else {
+                missing_metric.push(a.name.clone());
+                unpaired_old.push(a);
+                unpaired_new.push(b);
+            }
+        }
+        unpaired_old.sort();
+        unpaired_new.sort();
+        let failed = |failures: &[Failure], b: &Benchmark| failures.iter().any(|f| f.name == b.name);
         PairedBenchmarks {
-            cmps: cmps,
-            unpaired_old: ov.left,
-            unpaired_new: ov.right,
+            metric,
+            cmps,
+            unpaired_old: unpaired_old.into_iter().filter(|b| !failed(&failed_new, b)).collect(),
+            unpaired_new: unpaired_new.into_iter().filter(|b| !failed(&failed_old, b)).collect(),
+            missing_metric,
+            failed_old,
+            failed_new,
         }
     }
 }
 
+/// Returns the failures worth reporting: all benchmarks that failed, and
+/// those that were ignored but have a result in `other`.
+fn relevant_failures(mut failures: Vec<Failure>, other: &[Benchmark]) -> Vec<Failure> {
+    failures.retain(|f| f.status == Status::Failed || other.iter().any(|b| b.name == f.name));
+    failures.sort_by(|a, b| a.name.cmp(&b.name));
+    failures
+}
+
 impl PairedBenchmarks {
+    /// Returns the name of the metric the benchmarks are compared by (see
+    /// `Benchmarks::metric`).
+    pub fn metric(&self) -> &str {
+        &self.metric
+    }
+
+    /// Returns the unit of the metric the benchmarks are compared by, if
+    /// any benchmark has it.
+    pub fn unit(&self) -> Option<&str> {
+        self.cmps.iter()
+            .map(|c| &c.old)
+            .chain(&self.unpaired_old)
+            .chain(&self.unpaired_new)
+            .filter_map(|b| b.metric(&self.metric))
+            .map(|m| &*m.unit)
+            .next()
+    }
+
     /// Returns all pairwise benchmark comparisons.
     ///
     /// Each comparison provides access to the old and new benchmarks.
@@ -62,25 +371,187 @@ impl PairedBenchmarks {
     }
 
     /// Returns all benchmarks that were in the old set that were not found
-    /// in the new set.
+    /// in the new set, or that couldn't be compared (see `missing_metric`).
     pub fn missing_old(&self) -> &[Benchmark] {
         &self.unpaired_old
     }
 
     /// Returns all benchmarks that were in the new set that were not found
-    /// in the old set.
+    /// in the old set, or that couldn't be compared (see `missing_metric`).
     pub fn missing_new(&self) -> &[Benchmark] {
         &self.unpaired_new
     }
+
+    /// Returns the names of the benchmarks that are in both sets, but lack
+    /// the compared metric in at least one of them.
+    ///
+    /// These can't be compared, so they are in both `missing_old` and
+    /// `missing_new`.
+    pub fn missing_metric(&self) -> &[String] {
+        &self.missing_metric
+    }
+
+    /// Returns all benchmarks in the old set that failed or were ignored.
+    ///
+    /// These aren't included in `missing_new`, even if the new set has a
+    /// result for them.
+    pub fn failures_old(&self) -> &[Failure] {
+        &self.failed_old
+    }
+
+    /// Returns all benchmarks in the new set that failed or were ignored.
+    ///
+    /// These aren't included in `missing_old`, even if the old set has a
+    /// result for them.
+    pub fn failures_new(&self) -> &[Failure] {
+        &self.failed_new
+    }
+}
+
+/// A benchmark that was run, but didn't produce a result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Failure {
+    /// The name of the benchmark.
+    pub name: String,
+    /// Why the benchmark has no result.
+    pub status: Status,
+    /// The message the benchmark panicked with, if it is known.
+    pub message: Option<String>,
+    /// The bench target of the benchmark, if it is known (see
+    /// `Benchmark::target`).
+    pub target: Option<String>,
+    /// The crate of the benchmark, if it is known (see `Benchmark::krate`).
+    pub krate: Option<String>,
+}
+
+/// Why a benchmark has no result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Status {
+    /// The benchmark panicked, e.g., `test foo ... FAILED`.
+    Failed,
+    /// The benchmark was skipped, e.g., `test foo ... ignored`.
+    Ignored,
+}
+
+impl Failure {
+    /// Creates a failure without a message or origin.
+    pub fn new(name: &str, status: Status) -> Failure {
+        Failure {
+            name: name.to_string(),
+            status,
+            message: None,
+            target: None,
+            krate: None,
+        }
+    }
+
+    /// Describes the failure of this benchmark in the set named `set`, e.g.,
+    /// `foo failed in new: index out of bounds`.
+    pub fn describe(&self, set: &str) -> String {
+        match (self.status, &self.message) {
+            (Status::Failed, Some(message)) => format!("{} failed in {}: {}", self.name, set, message),
+            (Status::Failed, None) => format!("{} failed in {}", self.name, set),
+            (Status::Ignored, _) => format!("{} was ignored in {}", self.name, set),
+        }
+    }
 }
 
 /// All extractable data from a single micro-benchmark.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct Benchmark {
+    /// The name of the benchmark, e.g., `module::bench_name`.
     pub name: String,
-    pub ns: u64,
-    pub variance: u64,
-    pub throughput: Option<u64>,
+    /// The measurements of the benchmark by name, e.g., `time` (in
+    /// nanoseconds per iteration), `throughput` (in MB/s) or
+    /// `instructions`.
+    pub metrics: BTreeMap<String, Metric>,
+    /// The bench target whose output contained this benchmark, if it is
+    /// known, e.g., `foo` after `Running benches/foo.rs
+    /// (target/release/deps/foo-0b3ab7ed4bb2fd8a)`.
+    pub target: Option<String>,
+    /// The crate of the bench target, if it is known, with dashes replaced
+    /// by underscores like in the names of binaries.
+    pub krate: Option<String>,
+}
+
+/// Whether lower or higher values of a metric are better.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Direction {
+    /// Lower values are better, e.g., for times or instruction counts.
+    LowerIsBetter,
+    /// Higher values are better, e.g., for throughputs.
+    HigherIsBetter,
+}
+
+/// A named measurement of a benchmark, such as its time or a count of
+/// events.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Metric {
+    /// The measured value.
+    pub value: f64,
+    /// The variance reported for the value, or 0 for exact values.
+    pub variance: f64,
+    /// The unit of the value, e.g., `ns/iter` or `MB/s`.
+    pub unit: String,
+    /// Whether lower or higher values are better.
+    pub direction: Direction,
+}
+
+impl Metric {
+    /// Creates an exact metric, i.e., one without variance.
+    pub fn new(value: f64, unit: &str, direction: Direction) -> Metric {
+        Metric {
+            value,
+            variance: 0.0,
+            unit: unit.to_string(),
+            direction,
+        }
+    }
+
+    /// Creates the time taken by one iteration, in nanoseconds, as reported
+    /// by `cargo bench`.
+    pub fn time(ns: f64, variance: f64) -> Metric {
+        Metric {
+            variance,
+            ..Metric::new(ns, "ns/iter", Direction::LowerIsBetter)
+        }
+    }
+
+    /// Creates a throughput in MB/s.
+    pub fn throughput(mb_per_s: f64) -> Metric {
+        Metric::new(mb_per_s, "MB/s", Direction::HigherIsBetter)
+    }
+
+    /// Returns true if the unit of this metric is a time, e.g., `ns/iter`.
+    fn is_time(&self) -> bool {
+        self.unit == "ns" || self.unit.starts_with("ns/")
+    }
+
+    /// Returns true if the unit of this metric is a rate, e.g., `MB/s`.
+    fn is_rate(&self) -> bool {
+        self.unit.ends_with("/s")
+    }
+
+    /// Formats the value with thousands separators, followed by its
+    /// variance if `variance` is true.
+    pub fn fmt(&self, variance: bool) -> String {
+        if variance {
+            format!("{} (+/- {})", fmt_value(self.value), fmt_plain(self.variance))
+        } else {
+            fmt_value(self.value)
+        }
+    }
+
+    /// Formats the variance with thousands separators, e.g., to show it
+    /// apart from the value.
+    pub fn fmt_variance(&self) -> String {
+        fmt_value(self.variance)
+    }
 }
 
 impl Eq for Benchmark {}
@@ -93,13 +564,13 @@ impl PartialEq for Benchmark {
 
 impl Ord for Benchmark {
     fn cmp(&self, other: &Benchmark) -> cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+        self.name.cmp(&other.name)
     }
 }
 
 impl PartialOrd for Benchmark {
     fn partial_cmp(&self, other: &Benchmark) -> Option<cmp::Ordering> {
-        self.name.partial_cmp(&other.name)
+        Some(self.cmp(other))
     }
 }
 
@@ -110,102 +581,250 @@ lazy_static! {
         \s+\(\+/-\s+(?P<variance>[0-9,.]+)\)         # (+/- 4321)
         (?:\s+=\s+(?P<throughput>[0-9,.]+)\sMB/s)?   # =   2314 MB/s
     "##).unwrap();
+    /// Matches every line that claims to be the result of a benchmark,
+    /// whether or not the rest of it is well-formed.
+    static ref BENCHMARK_PREFIX: Regex = Regex::new(r"test\s+\S+\s+\.\.\.\s+bench:").unwrap();
+    static ref TIME_REGEX: Regex = Regex::new(r"bench:\s+[0-9,.]+\s+ns/iter").unwrap();
 }
 
 impl FromStr for Benchmark {
-    type Err = ();
+    type Err = ParseError;
 
     /// Parses a single benchmark line into a Benchmark.
-    fn from_str(line: &str) -> Result<Benchmark, ()> {
+    ///
+    /// If the line looks like the result of a benchmark but can't be
+    /// parsed, the error says why (see `ParseError::is_malformed`).
+    fn from_str(line: &str) -> ::std::result::Result<Benchmark, ParseError> {
         let caps = match BENCHMARK_REGEX.captures(line) {
-            None => return Err(()),
             Some(caps) => caps,
+            None if !BENCHMARK_PREFIX.is_match(line) => return Err(ParseError::not_a_benchmark()),
+            None if !TIME_REGEX.is_match(line) => {
+                return Err(ParseError::malformed("expected a time like `1,234 ns/iter` after `bench:`"))
+            }
+            None => {
+                return Err(ParseError::malformed("expected a variance like `(+/- 56)` after the time"))
+            }
         };
         let ns = match parse_commas(&caps["ns"]) {
-            None => return Err(()),
+            None => return Err(ParseError::malformed(format!("invalid time: {}", &caps["ns"]))),
             Some(ns) => ns,
         };
         let variance = match parse_commas(&caps["variance"]) {
-            None => return Err(()),
+            None => {
+                return Err(ParseError::malformed(format!("invalid variance: {}", &caps["variance"])))
+            }
             Some(variance) => variance,
         };
-        let throughput = caps.name("throughput").and_then(|m| parse_commas(m.as_str()));
-        Ok(Benchmark {
-            name: caps["name"].to_string(),
-            ns: ns,
-            variance: variance,
-            throughput: throughput,
-        })
+        let mut bench = Benchmark::new(&caps["name"]);
+        bench.metrics.insert("time".to_string(), Metric::time(ns as f64, variance as f64));
+        if let Some(throughput) = caps.name("throughput").and_then(|m| parse_commas(m.as_str())) {
+            bench.metrics.insert("throughput".to_string(), Metric::throughput(throughput as f64));
+        }
+        Ok(bench)
     }
 }
 
 impl Benchmark {
-    /// Compares an old benchmark (self) with a new benchmark.
-    pub fn compare(self, new: Benchmark) -> Comparison {
-        let diff_ns = new.ns as i64 - self.ns as i64;
-        let diff_ratio = diff_ns as f64 / self.ns as f64;
-        let speedup = 1.0 / (1.0 + diff_ratio);
-        Comparison {
-            old: self,
-            new: new,
-            diff_ns: diff_ns,
-            diff_ratio: diff_ratio,
-            speedup: speedup,
+    /// Creates a benchmark named `name` without any metrics, whose target
+    /// and crate aren't known.
+    pub fn new(name: &str) -> Benchmark {
+        Benchmark {
+            name: name.to_string(),
+            metrics: BTreeMap::new(),
+            target: None,
+            krate: None,
         }
     }
 
-    pub fn fmt_ns(&self, variance: bool) -> String {
-        let mut res = commafy(self.ns);
-        if variance {
-            res = format!("{} (+/- {})", res, self.variance);
+    /// Returns the metric named `name`, if this benchmark has it.
+    pub fn metric(&self, name: &str) -> Option<&Metric> {
+        self.metrics.get(name)
+    }
+
+    /// Returns the names of all metrics of this benchmark.
+    pub fn metric_names(&self) -> Vec<&str> {
+        self.metrics.keys().map(|k| &**k).collect()
+    }
+
+    /// Compares an old benchmark (self) with a new benchmark by the metric
+    /// named `metric`, if both have it.
+    pub fn compare(self, new: Benchmark, metric: &str) -> Option<Comparison> {
+        let (diff, diff_ratio, speedup, direction) = {
+            let (old_m, new_m) = (self.metric(metric)?, new.metric(metric)?);
+            let diff = new_m.value - old_m.value;
+            // Two zeros are no change, but any change from zero is infinite.
+            let diff_ratio = if diff == 0.0 { 0.0 } else { diff / old_m.value };
+            let speedup = match old_m.direction {
+                Direction::LowerIsBetter => 1.0 / (1.0 + diff_ratio),
+                Direction::HigherIsBetter => 1.0 + diff_ratio,
+            };
+            (diff, diff_ratio, speedup, old_m.direction)
+        };
+        Some(Comparison {
+            old: self,
+            new,
+            metric: metric.to_string(),
+            direction,
+            diff,
+            diff_ratio,
+            speedup,
+        })
+    }
+
+    /// Multiplies the metrics of this benchmark that are measured in time by
+    /// `to / from`.
+    ///
+    /// Dividing by `from` first turns a time of `from`, like that of the
+    /// reference benchmark, into exactly `to`. Rates like throughput are inversely proportional to time, so
+    /// they are multiplied by `from / to` instead.
+    fn scale(&mut self, from: f64, to: f64) {
+        for metric in self.metrics.values_mut() {
+            if metric.is_time() {
+                metric.value = metric.value / from * to;
+                metric.variance = metric.variance / from * to;
+            } else if metric.is_rate() {
+                metric.value = metric.value / to * from;
+                metric.variance = metric.variance / to * from;
+            }
         }
-        if let Some(throughput) = self.throughput {
-            res = format!("{} ({} MB/s)", res, throughput);
+    }
+
+    /// Formats the metric named `name` with thousands separators, followed
+    /// by its variance (if `variance` is true), or `n/a` if this benchmark
+    /// lacks it.
+    ///
+    /// Like in the output of `cargo bench`, the time is also followed by the
+    /// throughput, if there is one.
+    pub fn fmt_metric(&self, name: &str, variance: bool) -> String {
+        let mut res = match self.metric(name) {
+            None => return "n/a".to_string(),
+            Some(metric) => metric.fmt(variance),
+        };
+        if let (true, Some(throughput)) = (name == "time", self.metric("throughput")) {
+            res = format!("{} ({} {})", res, fmt_plain(throughput.value), throughput.unit);
         }
         res
     }
 }
 
-/// A comparison between an old and a new benchmark.
+/// A comparison between an old and a new benchmark by one of their metrics.
 /// All differences are reported in terms of measuring improvements
 /// (negative) or regressions (positive). That is, if an old benchmark
 /// is slower than a new benchmark, then the difference is negative.
 /// Conversely, if an old benchmark is faster than a new benchmark,
-/// then the difference is positive.
+/// then the difference is positive. For metrics where higher values are
+/// better, such as throughput, this is the other way around.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct Comparison {
+    /// The old benchmark.
     pub old: Benchmark,
+    /// The new benchmark.
     pub new: Benchmark,
-    pub diff_ns: i64,
+    /// The name of the metric the benchmarks are compared by, e.g., `time`.
+    pub metric: String,
+    /// Whether lower or higher values of the metric are better.
+    pub direction: Direction,
+    /// The difference of the metric, i.e., its new value minus its old one.
+    pub diff: f64,
+    /// The difference relative to the old value, e.g., `0.1` is 10% higher.
+    /// It is infinite if the old value is 0 and the new one isn't.
     pub diff_ratio: f64,
+    /// How many times better the new value is than the old one, e.g., how
+    /// many times faster the new benchmark is.
     pub speedup: f64,
 }
 
 impl Comparison {
-    /// Convert this comparison to a formatted row useful for printing.
+    /// Returns the old value of the compared metric.
+    pub fn old_value(&self) -> f64 {
+        self.old.metrics[&self.metric].value
+    }
+
+    /// Returns the new value of the compared metric.
+    pub fn new_value(&self) -> f64 {
+        self.new.metrics[&self.metric].value
+    }
+
+    /// Returns true if the metric got worse, e.g., if the new benchmark is
+    /// slower than the old one.
+    pub fn is_regression(&self) -> bool {
+        match self.direction {
+            Direction::LowerIsBetter => self.diff > 0.0,
+            Direction::HigherIsBetter => self.diff < 0.0,
+        }
+    }
+
+    /// Returns true if this is a regression that should be treated as a
+    /// failure.
     ///
-    /// The columns of the row are as follows: the name of the benchmark being
-    /// compared, the old measurement, the new measurement, the measurement
-    /// difference and the percent measurement difference. Negative differences
-    /// imply an improvement in performance from old to new.
-    pub fn to_row(&self, variance: bool, regression: bool) -> Row {
-        let name = &self.old.name;
-        let fst_ns = self.old.fmt_ns(variance);
-        let snd_ns = self.new.fmt_ns(variance);
-        let diff_ratio = format!("{:.2}%", self.diff_ratio * 100f64);
-        let speedup = format!("x {:.2}", self.speedup);
-        let diff_ns = {
-            let diff_ns = commafy(self.diff_ns.abs() as u64);
-            if self.diff_ns < 0 {
-                format!("-{}", diff_ns)
-            } else {
-                diff_ns
-            }
-        };
-        if regression {
-            row![Fr->name, Fr->fst_ns, Fr->snd_ns, rFr->diff_ns, rFr->diff_ratio, rFr->speedup]
-        } else {
-            row![Fg->name, Fg->fst_ns, Fg->snd_ns, rFg->diff_ns, rFg->diff_ratio, rFg->speedup]
+    /// Without a threshold every regression fails. With one, only
+    /// regressions whose percent change is at least the threshold fail.
+    pub fn fails(&self, threshold: Option<u8>) -> bool {
+        self.is_regression() && threshold.is_none_or(|t| self.abs_percent() >= t)
+    }
+
+    /// Returns the absolute percent change, truncated to a whole number.
+    ///
+    /// This is what thresholds are compared against.
+    pub fn abs_percent(&self) -> u8 {
+        (self.diff_ratio * 100f64).abs().trunc() as u8
+    }
+
+    /// Compares the metric named `name` of the old and new benchmarks, if
+    /// both have it.
+    pub fn metric(&self, name: &str) -> Option<MetricComparison> {
+        let old = self.old.metric(name)?.clone();
+        let new = self.new.metric(name)?.clone();
+        let diff = new.value - old.value;
+        Some(MetricComparison {
+            name: name.to_string(),
+            diff,
+            diff_ratio: if old.value == 0.0 { None } else { Some(diff / old.value) },
+            old,
+            new,
+        })
+    }
+
+    /// Formats the difference of the compared metric with thousands
+    /// separators.
+    pub fn fmt_diff(&self) -> String {
+        fmt_value(self.diff)
+    }
+}
+
+/// The change of one metric between an old and a new benchmark.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct MetricComparison {
+    /// The name of the metric.
+    pub name: String,
+    /// The old value.
+    pub old: Metric,
+    /// The new value.
+    pub new: Metric,
+    /// The difference, i.e., `new.value - old.value`.
+    pub diff: f64,
+    /// The difference relative to the old value, or `None` if the old value
+    /// is 0.
+    pub diff_ratio: Option<f64>,
+}
+
+impl MetricComparison {
+    /// Returns true if the metric got worse, taking into account whether
+    /// lower or higher values are better.
+    pub fn is_regression(&self) -> bool {
+        match self.old.direction {
+            Direction::LowerIsBetter => self.diff > 0.0,
+            Direction::HigherIsBetter => self.diff < 0.0,
+        }
+    }
+
+    /// Formats the percent change, or `n/a` if there is no relative change.
+    pub fn fmt_percent(&self) -> String {
+        match self.diff_ratio {
+            Some(ratio) => format!("{:.2}%", ratio * 100f64),
+            None => "n/a".to_string(),
         }
     }
 }
@@ -260,7 +879,7 @@ impl<T> Overlap<T> {
         overlap.reverse();
         Overlap {
             left: rleft,
-            overlap: overlap,
+            overlap,
             right: rright,
         }
     }
@@ -276,6 +895,30 @@ fn drop_commas(s: &str) -> String {
     s.chars().take_while(|&c| c != '.').filter(|&c| c != ',').collect()
 }
 
+/// Formats a value with thousands separators and, unless it is whole, up
+/// to two decimals, e.g., `-1,234.5`.
+fn fmt_value(value: f64) -> String {
+    let hundredths = (value.abs() * 100.0).round() as u64;
+    let sign = if value < 0.0 && hundredths > 0 { "-" } else { "" };
+    match hundredths % 100 {
+        0 => format!("{}{}", sign, commafy(hundredths / 100)),
+        fraction => {
+            let fraction = format!("{:02}", fraction);
+            format!("{}{}.{}", sign, commafy(hundredths / 100), fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+/// Formats a value without thousands separators and, unless it is whole,
+/// with up to two decimals, e.g., `1234.5`.
+fn fmt_plain(value: f64) -> String {
+    let res = format!("{:.2}", value);
+    match res.trim_end_matches('0').trim_end_matches('.') {
+        "-0" => "0".to_string(),
+        res => res.to_string(),
+    }
+}
+
 /// Commafy a number as a string.
 fn commafy(n: u64) -> String {
     let mut with_commas = vec![];
@@ -297,6 +940,14 @@ fn commafy(n: u64) -> String {
     String::from_utf8(with_commas).unwrap()
 }
 
+/// Returns a benchmark named `name` that took `ns` ns/iter, for tests.
+#[cfg(test)]
+pub fn bench(name: &str, ns: u64) -> Benchmark {
+    let mut bench = Benchmark::new(name);
+    bench.metrics.insert("time".to_string(), Metric::time(ns as f64, 0.0));
+    bench
+}
+
 #[cfg(test)]
 mod tests {
     mod overlap {
@@ -378,7 +1029,7 @@ mod tests {
                 let commafied = commafy(n);
                 let mut commafied = commafied.split(',');
                 let s = commafied.next().unwrap();
-                if s.len() == 0 || s.len() > 3 {
+                if s.is_empty() || s.len() > 3 {
                     return false;
                 }
                 for s in commafied {
@@ -399,7 +1050,7 @@ mod tests {
     }
 
     mod benchmark {
-        use super::super::Benchmark;
+        use super::super::{Benchmark, Direction, Metric};
         use quickcheck::Arbitrary;
         use quickcheck::Gen;
         use rand::Rng;
@@ -408,33 +1059,33 @@ mod tests {
 
         impl Arbitrary for Benchmark {
             fn arbitrary<G: Gen>(g: &mut G) -> Self {
-                let (ns, variance, throughput): (u64, u64, Option<u64>) = Arbitrary::arbitrary(g);
-                let name = {
+                let (ns, variance, throughput): (u32, u32, Option<u32>) = Arbitrary::arbitrary(g);
+                let name: String = {
                     let size = g.size();
                     let size = g.gen_range(1, size);
                     iter::repeat(()).map(|()| g.sample(Alphanumeric)).take(size).collect()
                 };
-                Benchmark {
-                    name: name,
-                    ns: ns,
-                    variance: variance,
-                    throughput: throughput,
+                let mut bench = Benchmark::new(&name);
+                bench.metrics.insert("time".to_string(), Metric::time(ns as f64, variance as f64));
+                if let Some(throughput) = throughput {
+                    bench.metrics.insert("throughput".to_string(), Metric::throughput(throughput as f64));
                 }
+                bench
             }
         }
 
         fn deep_eq(b1: &Benchmark, b2: &Benchmark) -> bool {
-            b1.name == b2.name && b1.variance == b2.variance && b1.ns == b2.ns &&
-            b1.throughput == b2.throughput
+            b1.name == b2.name && b1.metrics == b2.metrics
         }
 
         fn as_string(b: &Benchmark) -> String {
+            let time = &b.metrics["time"];
             let res = format!("test {} ... bench: {} ns/iter (+/- {})",
                               b.name,
-                              b.ns,
-                              b.variance);
-            if let Some(throughput) = b.throughput {
-                format!("{} = {} MB/s", res, throughput)
+                              time.value,
+                              time.variance);
+            if let Some(throughput) = b.metric("throughput") {
+                format!("{} = {} MB/s", res, throughput.value)
             } else {
                 res
             }
@@ -448,6 +1099,138 @@ mod tests {
                     false
                 }
             }
+
+            fn throughput_regression(b1: Benchmark, b2: Benchmark) -> bool {
+                let t1 = b1.metric("throughput").map(|t| t.value);
+                let t2 = b2.metric("throughput").map(|t| t.value);
+                let cmp = b1.compare(b2, "time").unwrap();
+                match (t1, t2) {
+                    (Some(t1), Some(t2)) => {
+                        cmp.metric("throughput").unwrap().is_regression() == (t2 < t1)
+                    }
+                    _ => cmp.metric("throughput").is_none(),
+                }
+            }
+        }
+
+        #[test]
+        fn metrics() {
+            let mut b1: Benchmark = "test fib ... bench: 1,000 ns/iter (+/- 40) = 300 MB/s"
+                .parse()
+                .unwrap();
+            let mut b2: Benchmark = "test fib ... bench: 900 ns/iter (+/- 35) = 330 MB/s"
+                .parse()
+                .unwrap();
+            b1.metrics.insert("allocs".to_string(), Metric::new(10.0, "count", Direction::LowerIsBetter));
+            b2.metrics.insert("allocs".to_string(), Metric::new(12.0, "count", Direction::LowerIsBetter));
+            b1.metrics.insert("misses".to_string(), Metric::new(0.0, "count", Direction::LowerIsBetter));
+            b2.metrics.insert("misses".to_string(), Metric::new(3.0, "count", Direction::LowerIsBetter));
+            assert_eq!(b1.metric_names(), vec!["allocs", "misses", "throughput", "time"]);
+            assert_eq!(b1.fmt_metric("time", true), "1,000 (+/- 40) (300 MB/s)");
+
+            let cmp = b1.clone().compare(b2.clone(), "time").unwrap();
+            let time = cmp.metric("time").unwrap();
+            assert_eq!((time.diff, time.is_regression()), (-100.0, false));
+            let throughput = cmp.metric("throughput").unwrap();
+            assert_eq!((throughput.diff, throughput.is_regression()), (30.0, false));
+            let allocs = cmp.metric("allocs").unwrap();
+            assert_eq!((allocs.diff, allocs.is_regression()), (2.0, true));
+            let misses = cmp.metric("misses").unwrap();
+            assert_eq!((misses.diff_ratio, misses.fmt_percent()), (None, "n/a".to_string()));
+            assert!(cmp.metric("nope").is_none());
+
+            let cmp = b1.compare(b2, "throughput").unwrap();
+            assert!(!cmp.is_regression());
+            assert!((cmp.speedup - 1.1).abs() < 1e-9);
+        }
+
+        #[test]
+        fn scale() {
+            let mut b: Benchmark = "test sum ... bench: 1,151 ns/iter (+/- 9) = 3560 MB/s"
+                .parse()
+                .unwrap();
+            b.metrics.get_mut("time").unwrap().value = 1150.7;
+            b.scale(1150.7, 1012.3);
+            assert_eq!(b.metrics["time"].value, 1012.3);
+            assert!((b.metrics["throughput"].value - 3560.0 * 1150.7 / 1012.3).abs() < 1e-9);
+        }
+
+        #[test]
+        fn values() {
+            use super::super::fmt_value;
+            assert_eq!(fmt_value(1234567.0), "1,234,567");
+            assert_eq!(fmt_value(-1234.5), "-1,234.5");
+            assert_eq!(fmt_value(0.256), "0.26");
+            assert_eq!(fmt_value(-0.001), "0");
+        }
+
+        #[test]
+        fn plain_values() {
+            use super::super::fmt_plain;
+            assert_eq!(fmt_plain(28652.699), "28652.7");
+            assert_eq!(fmt_plain(100.0), "100");
+            assert_eq!(fmt_plain(-0.001), "0");
+        }
+    }
+
+    mod dedup {
+        use super::super::{bench, Benchmark, Benchmarks, Duplicates};
+
+        fn in_target(name: &str, ns: u64, target: &str) -> Benchmark {
+            let mut b = bench(name, ns);
+            b.target = Some(target.to_string());
+            b
+        }
+
+        fn dedup(policy: Duplicates) -> Vec<(String, f64)> {
+            let old = vec![in_target("fib", 30, "a"),
+                           in_target("sort", 5, "a"),
+                           in_target("fib", 10, "b"),
+                           in_target("fib", 20, "c")];
+            let mut benches = Benchmarks::from((old, vec![in_target("fib", 10, "a")]));
+            benches.dedup(policy).unwrap();
+            benches.old.into_iter().map(|b| (b.name, b.metrics["time"].value)).collect()
+        }
+
+        #[test]
+        fn policies() {
+            let fib = |ns| vec![("fib".to_string(), ns), ("sort".to_string(), 5.0)];
+            assert_eq!(dedup(Duplicates::First), fib(30.0));
+            assert_eq!(dedup(Duplicates::Last), fib(20.0));
+            assert_eq!(dedup(Duplicates::Min), fib(10.0));
+            assert_eq!(dedup(Duplicates::Median), fib(20.0));
+            assert_eq!(dedup(Duplicates::Target),
+                       vec![("a::fib".to_string(), 30.0),
+                            ("sort".to_string(), 5.0),
+                            ("b::fib".to_string(), 10.0),
+                            ("c::fib".to_string(), 20.0)]);
+        }
+
+        #[test]
+        fn error() {
+            let old = vec![in_target("fib", 30, "a"), in_target("fib", 10, "a")];
+            let mut benches = Benchmarks::from((old.clone(), vec![]));
+            assert_eq!(benches.dedup(Duplicates::Error).unwrap_err().to_string(),
+                       "benchmark fib appears 2 times in old benchmarks");
+            let mut benches = Benchmarks::from((old, vec![]));
+            assert!(benches.dedup(Duplicates::Target).is_err());
+        }
+
+        #[test]
+        fn target_in_several_crates() {
+            let in_crate = |ns, krate: &str| {
+                let mut b = in_target("fib", ns, "search");
+                b.krate = Some(krate.to_string());
+                b
+            };
+            let mut benches = Benchmarks::from((vec![in_crate(30, "x"), in_crate(10, "y")],
+                                                vec![in_crate(20, "x")]));
+            assert_eq!(benches.duplicates().iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+                       vec!["benchmark fib appears 2 times in old benchmarks"]);
+            benches.dedup(Duplicates::Target).unwrap();
+            let names = |set: &[Benchmark]| set.iter().map(|b| b.name.clone()).collect::<Vec<_>>();
+            assert_eq!(names(&benches.old), vec!["x::search::fib", "y::search::fib"]);
+            assert_eq!(names(&benches.new), vec!["x::search::fib"]);
         }
     }
 }
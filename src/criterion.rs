@@ -0,0 +1,146 @@
+/*!
+Reading the results of benchmarks run with
+[Criterion.rs](https://github.com/bheisler/criterion.rs).
+
+Criterion doesn't print results in a stable format, but it saves them as
+JSON files in its output directory, `target/criterion` by default.
+*/
+
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+use serde_json;
+
+use benchmark::{Benchmark, Direction, Metric};
+use error::{Error, Result};
+
+/// The description of a benchmark in its `benchmark.json`.
+#[derive(Debug, Deserialize)]
+struct BenchmarkInfo {
+    full_id: String,
+    throughput: Option<Throughput>,
+}
+
+/// The throughput of a benchmark, in bytes or elements per iteration.
+#[derive(Debug, Deserialize)]
+struct Throughput {
+    #[serde(rename = "Bytes")]
+    bytes: Option<u64>,
+    #[serde(rename = "BytesDecimal")]
+    bytes_decimal: Option<u64>,
+    #[serde(rename = "Elements")]
+    elements: Option<u64>,
+}
+
+/// The statistics of a benchmark in its `estimates.json`.
+#[derive(Debug, Deserialize)]
+struct Estimates {
+    mean: Estimate,
+    median: Option<Estimate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Estimate {
+    confidence_interval: ConfidenceInterval,
+    point_estimate: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfidenceInterval {
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+impl Estimate {
+    /// Converts the estimate to a time, whose variance is half the width of
+    /// the confidence interval.
+    fn to_metric(&self) -> Metric {
+        let ci = &self.confidence_interval;
+        Metric::time(self.point_estimate, (ci.upper_bound - ci.lower_bound) / 2.0)
+    }
+}
+
+/// Reads the benchmarks that Criterion.rs saved under a baseline.
+///
+/// `dir` is a Criterion output directory, usually `target/criterion`. Each
+/// benchmark in it has a directory per baseline, where `new` holds the
+/// results of the latest run and other names are baselines saved with
+/// `--save-baseline`. Benchmarks without results for `baseline` are
+/// skipped.
+///
+/// Every benchmark is named by its full ID, e.g., `group/function/input`.
+/// Its time is the median estimate (or the mean, for old versions of
+/// Criterion that didn't record one), and its variance is half the width of
+/// that estimate's confidence interval. A throughput in bytes is converted
+/// to MB/s, like libtest reports it. The mean estimate is available as the
+/// metric `mean`, and a throughput in elements as `elements` (in elements
+/// per second).
+pub fn read_dir<P: AsRef<Path>>(dir: P, baseline: &str) -> Result<Vec<Benchmark>> {
+    let mut benches = vec![];
+    walk(dir.as_ref(), baseline, &mut benches)?;
+    benches.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(benches)
+}
+
+fn walk(dir: &Path, baseline: &str, benches: &mut Vec<Benchmark>) -> Result<()> {
+    let results = dir.join(baseline);
+    if results.join("estimates.json").is_file() {
+        benches.push(read_benchmark(&results)?);
+        return Ok(());
+    }
+    let entries = fs::read_dir(dir).map_err(|err| {
+        Error::OpenFile {
+            path: dir.to_path_buf(),
+            err,
+        }
+    })?;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            walk(&entry.path(), baseline, benches)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads one benchmark from the directory holding its results for a
+/// baseline.
+fn read_benchmark(results: &Path) -> Result<Benchmark> {
+    let info: BenchmarkInfo = read_json(&results.join("benchmark.json"))?;
+    let estimates: Estimates = read_json(&results.join("estimates.json"))?;
+    let estimate = estimates.median.as_ref().unwrap_or(&estimates.mean);
+    let ns = estimate.point_estimate;
+    let mut bench = Benchmark::new(&info.full_id);
+    bench.metrics.insert("time".to_string(), estimate.to_metric());
+    bench.metrics.insert("mean".to_string(), estimates.mean.to_metric());
+    let bytes = info.throughput.as_ref().and_then(|t| t.bytes.or(t.bytes_decimal)).filter(|_| ns > 0.0);
+    if let Some(bytes) = bytes {
+        let mb_per_s = bytes as f64 * 1000.0 / ns;
+        bench.metrics.insert("throughput".to_string(), Metric::throughput(mb_per_s));
+    }
+    let elements = info.throughput.and_then(|t| t.elements).filter(|_| ns > 0.0);
+    if let Some(elements) = elements {
+        let per_second = elements as f64 * 1e9 / ns;
+        bench.metrics.insert("elements".to_string(),
+                             Metric::new(per_second, "elem/s", Direction::HigherIsBetter));
+    }
+    Ok(bench)
+}
+
+fn read_json<T>(path: &Path) -> Result<T>
+    where T: for<'de> ::serde::Deserialize<'de>
+{
+    let file = File::open(path).map_err(|err| {
+        Error::OpenFile {
+            path: path.to_path_buf(),
+            err,
+        }
+    })?;
+    serde_json::from_reader(BufReader::new(file)).map_err(|err| {
+        Error::InvalidJson {
+            path: path.to_path_buf(),
+            err,
+        }
+    })
+}
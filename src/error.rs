@@ -5,32 +5,147 @@ use std::path::PathBuf;
 use std::result;
 
 use regex;
+use serde_json;
 
+/// A type alias for `Result<T, benchcmp::Error>`.
 pub type Result<T> = result::Result<T, Error>;
 
+/// A line of benchmark output that couldn't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The number of the line in its input, starting at 1, or 0 if it isn't
+    /// known.
+    pub line: usize,
+    /// Why the line couldn't be parsed.
+    pub reason: String,
+    malformed: bool,
+}
+
+impl ParseError {
+    /// Creates the error for a line that isn't the result of a benchmark.
+    pub(crate) fn not_a_benchmark() -> ParseError {
+        ParseError {
+            line: 0,
+            reason: "not a benchmark result".to_string(),
+            malformed: false,
+        }
+    }
+
+    /// Creates the error for a line that looks like the result of a
+    /// benchmark, but couldn't be parsed.
+    pub(crate) fn malformed<S: Into<String>>(reason: S) -> ParseError {
+        ParseError {
+            line: 0,
+            reason: reason.into(),
+            malformed: true,
+        }
+    }
+
+    /// Returns true if the line looks like the result of a benchmark, but
+    /// couldn't be parsed. Other lines simply aren't benchmark results.
+    pub fn is_malformed(&self) -> bool {
+        self.malformed
+    }
+}
+
+impl error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            self.reason.fmt(f)
+        } else {
+            write!(f, "line {}: {}", self.line, self.reason)
+        }
+    }
+}
+
+/// An error that can occur while reading or comparing benchmarks.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
+    /// An error that occurred while compiling a regular expression.
     Regex(regex::Error),
+    /// An I/O error that occurred while reading benchmark output.
     Io(io::Error),
-    OpenFile { path: PathBuf, err: io::Error },
+    /// An error that occurred while opening the file at `path`.
+    OpenFile {
+        /// The path of the file that couldn't be opened.
+        path: PathBuf,
+        /// The underlying I/O error.
+        err: io::Error,
+    },
+    /// The reference benchmark to normalize by is missing from a set.
+    MissingReference {
+        /// The name of the reference benchmark.
+        name: String,
+        /// The set that lacks it, either `old` or `new`.
+        set: &'static str,
+    },
+    /// The reference benchmark to normalize by took 0 ns/iter in a set, so
+    /// it can't be divided by.
+    ZeroReference {
+        /// The name of the reference benchmark.
+        name: String,
+        /// The set in which it took 0 ns/iter, either `old` or `new`.
+        set: &'static str,
+    },
+    /// A set contains several benchmarks with the same name, which the
+    /// policy for duplicates doesn't resolve (see `Benchmarks::dedup`).
+    DuplicateBenchmark {
+        /// The name of the benchmark.
+        name: String,
+        /// How many times the name occurs.
+        count: usize,
+        /// The set that contains it, either `old` or `new`.
+        set: &'static str,
+    },
+    /// A benchmark lacks the metric to compare by.
+    MissingMetric {
+        /// The name of the metric.
+        name: String,
+        /// The name of the benchmark that lacks it.
+        benchmark: String,
+    },
+    /// The JSON file at `path`, written by a benchmark harness, couldn't be
+    /// read.
+    InvalidJson {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying JSON error.
+        err: serde_json::Error,
+    },
+    /// A line of benchmark output couldn't be parsed, in strict mode.
+    Parse {
+        /// A description of the input, e.g., its path.
+        input: String,
+        /// The error for the line.
+        err: ParseError,
+    },
+    /// No parser is registered for the requested input format.
+    UnknownFormat {
+        /// The name of the requested format.
+        name: String,
+        /// The names of the formats that are registered.
+        known: Vec<String>,
+    },
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            Error::Regex(ref err) => err.description(),
-            Error::Io(ref err) => err.description(),
-            Error::OpenFile { ref err, .. } => err.description(),
+            Error::Regex(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            Error::OpenFile { ref err, .. } => Some(err),
+            Error::InvalidJson { ref err, .. } => Some(err),
+            Error::Parse { ref err, .. } => Some(err),
+            Error::DuplicateBenchmark { .. } |
+            Error::MissingMetric { .. } |
+            Error::MissingReference { .. } |
+            Error::ZeroReference { .. } |
+            Error::UnknownFormat { .. } => None,
         }
     }
-
-    fn cause(&self) -> Option<&error::Error> {
-        Some(match *self {
-            Error::Regex(ref err) => err,
-            Error::Io(ref err) => err,
-            Error::OpenFile { ref err, .. } => err,
-        })
-    }
 }
 
 impl fmt::Display for Error {
@@ -39,6 +154,26 @@ impl fmt::Display for Error {
             Error::Regex(ref err) => err.fmt(f),
             Error::Io(ref err) => err.fmt(f),
             Error::OpenFile { ref path, ref err } => write!(f, "{}: {}", err, path.display()),
+            Error::Parse { ref input, ref err } => write!(f, "{}: {}", input, err),
+            Error::DuplicateBenchmark { ref name, count, set } => {
+                write!(f, "benchmark {} appears {} times in {} benchmarks", name, count, set)
+            }
+            Error::MissingMetric { ref name, ref benchmark } => {
+                write!(f, "benchmark {} has no {} metric", benchmark, name)
+            }
+            Error::InvalidJson { ref path, ref err } => write!(f, "{}: {}", err, path.display()),
+            Error::MissingReference { ref name, set } => {
+                write!(f, "reference benchmark {} not found in {} benchmarks", name, set)
+            }
+            Error::ZeroReference { ref name, set } => {
+                write!(f, "reference benchmark {} in {} benchmarks took 0 ns/iter", name, set)
+            }
+            Error::UnknownFormat { ref name, ref known } => {
+                write!(f,
+                       "unknown input format {} (expected auto or one of: {})",
+                       name,
+                       known.join(", "))
+            }
         }
     }
 }
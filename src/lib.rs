@@ -0,0 +1,374 @@
+/*!
+This crate provides the parsing and pairing logic behind `cargo benchcmp`, so
+that Rust micro-benchmark results can be compared from other tools.
+
+The typical flow is to parse the output of two `cargo bench` runs with
+[`parse_buffer`](fn.parse_buffer.html), combine them into a
+[`Benchmarks`](struct.Benchmarks.html) and pair them up by name, which yields
+a [`PairedBenchmarks`](struct.PairedBenchmarks.html). Each
+[`Comparison`](struct.Comparison.html) in it describes how one benchmark
+changed, and the benchmarks that couldn't be paired are available as well.
+
+# Example
+
+```
+extern crate benchcmp;
+
+use benchcmp::Benchmarks;
+
+# fn main() {
+let old = "test fib ... bench:       1,200 ns/iter (+/- 40)";
+let new = "test fib ... bench:         900 ns/iter (+/- 35)";
+
+let old = benchcmp::parse_buffer(old.as_bytes()).unwrap();
+let new = benchcmp::parse_buffer(new.as_bytes()).unwrap();
+let paired = Benchmarks::from((old, new)).paired();
+
+let cmp = &paired.comparisons()[0];
+assert_eq!(cmp.old.name, "fib");
+assert_eq!((&*cmp.metric, cmp.diff), ("time", -300.0));
+assert!(!cmp.is_regression());
+assert!(paired.missing_old().is_empty());
+# }
+```
+
+Output in other formats, such as libtest's JSON output, can be parsed with
+the parsers in a [`Registry`](struct.Registry.html), which can also pick one
+automatically. Custom formats are supported by implementing
+[`Parser`](trait.Parser.html) and registering it.
+
+The paired benchmarks can be rendered in any of the output formats of
+`cargo benchcmp` with the reporters in the [`report`](report/index.html)
+module, or in a custom format by implementing
+[`Reporter`](trait.Reporter.html).
+
+Results saved by Criterion.rs are read from its output directory with
+[`criterion::read_dir`](criterion/fn.read_dir.html).
+
+When the benchmarks to compare come from a single run and are distinguished
+by a name prefix, use [`split_benchmarks`](fn.split_benchmarks.html) to
+separate them before pairing, or
+[`split_prefixes`](fn.split_prefixes.html) for more than two prefixes. If
+the variant is elsewhere in the name, e.g., `parse_json/simd`, use
+[`split_variants`](fn.split_variants.html) with a regex that finds it.
+Failures are split the same way with
+[`split_failures`](fn.split_failures.html) and
+[`split_failure_variants`](fn.split_failure_variants.html).
+
+The [`scaling`](scaling/index.html) module groups benchmarks that only differ
+by a numeric parameter, such as `sort_10` and `sort_1000`, and estimates how
+their results grow with it.
+*/
+
+#![deny(missing_docs)]
+
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate prettytable;
+extern crate regex;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate term;
+#[cfg(test)]
+#[macro_use]
+extern crate quickcheck;
+#[cfg(test)]
+extern crate rand;
+
+use regex::Regex;
+use std::io::BufRead;
+
+pub use benchmark::{Benchmark, Benchmarks, Comparison, Direction, Duplicates, Failure, Metric,
+                    MetricComparison, PairedBenchmarks, Qualifier, Status};
+pub use error::{Error, ParseError, Result};
+pub use parser::{Divan, Hyperfine, Iai, Libtest, LibtestJson, Parsed, Parser, Registry};
+pub use report::{Context, Reporter};
+
+mod benchmark;
+pub mod criterion;
+mod error;
+mod parser;
+pub mod report;
+pub mod scaling;
+
+/// Parse benchmarks from a buffered reader.
+///
+/// Every line that looks like the result of a benchmark run by `cargo bench`
+/// produces one benchmark. All other lines are ignored. This is a shortcut
+/// for the [`Libtest`](struct.Libtest.html) parser. To handle other formats,
+/// use a [`Registry`](struct.Registry.html).
+pub fn parse_buffer<B: BufRead>(mut buffer: B) -> Result<Vec<Benchmark>> {
+    Libtest.parse(&mut buffer)
+}
+
+/// Splits benchmarks from one source with two prefixes.
+///
+/// The first prefix identifies benchmarks in the old set and the second
+/// prefix identifies benchmarks in the new set. The prefixes are stripped
+/// from the names of the benchmarks, so that they can be paired up.
+/// Benchmarks matching neither prefix are dropped.
+pub fn split_benchmarks(vec: Vec<Benchmark>,
+                        old_prefix: &str,
+                        new_prefix: &str)
+                        -> (Vec<Benchmark>, Vec<Benchmark>) {
+    let mut sets = split_prefixes(vec, &[old_prefix, new_prefix]);
+    let b_new = sets.pop().unwrap();
+    let b_old = sets.pop().unwrap();
+    (b_old, b_new)
+}
+
+/// Splits benchmarks from one source with any number of prefixes.
+///
+/// Returns one set of benchmarks per prefix, in the same order. Each
+/// benchmark goes to the set of the first prefix it starts with, which is
+/// stripped from its name. Benchmarks matching no prefix are dropped.
+pub fn split_prefixes<S: AsRef<str>>(vec: Vec<Benchmark>, prefixes: &[S]) -> Vec<Vec<Benchmark>> {
+    split_by(vec, prefixes.len(), |b| &mut b.name, |name| prefix_of(name, prefixes))
+}
+
+/// Splits benchmarks from one source into the variants found by a regex.
+///
+/// Each benchmark is split into its variant and the key it's paired by with
+/// [`variant_of`](fn.variant_of.html), and renamed to that key. Returns one
+/// set of benchmarks per variant in `variants`, in the same order.
+/// Benchmarks that `re` doesn't match, or whose variant isn't listed, are
+/// dropped.
+pub fn split_variants<S: AsRef<str>>(vec: Vec<Benchmark>,
+                                     re: &Regex,
+                                     variants: &[S])
+                                     -> Vec<Vec<Benchmark>> {
+    split_by(vec, variants.len(), |b| &mut b.name, |name| variant_index(name, re, variants))
+}
+
+/// Splits the failures from one source with any number of prefixes.
+///
+/// This is [`split_prefixes`](fn.split_prefixes.html) for failures, so that
+/// they end up in the same sets as the benchmarks from the same source.
+pub fn split_failures<S: AsRef<str>>(vec: Vec<Failure>, prefixes: &[S]) -> Vec<Vec<Failure>> {
+    split_by(vec, prefixes.len(), |f| &mut f.name, |name| prefix_of(name, prefixes))
+}
+
+/// Splits the failures from one source into the variants found by a regex.
+///
+/// This is [`split_variants`](fn.split_variants.html) for failures, so that
+/// they end up in the same sets as the benchmarks from the same source.
+pub fn split_failure_variants<S: AsRef<str>>(vec: Vec<Failure>,
+                                             re: &Regex,
+                                             variants: &[S])
+                                             -> Vec<Vec<Failure>> {
+    split_by(vec, variants.len(), |f| &mut f.name, |name| variant_index(name, re, variants))
+}
+
+/// Returns the variant of a benchmark name and the key it's paired by.
+///
+/// The variant is the text of the capture group named `variant` in `re`,
+/// and the key is the name with the whole match of `re` removed. For
+/// example, the regex `/(?P<variant>\w+)$` splits `parse_json/simd` into
+/// the variant `simd` and the key `parse_json`. Returns `None` if `re`
+/// doesn't match the name, or the `variant` group doesn't take part in the
+/// match.
+pub fn variant_of<'a>(name: &'a str, re: &Regex) -> Option<(&'a str, String)> {
+    let caps = re.captures(name)?;
+    let variant = caps.name("variant")?.as_str();
+    let whole = caps.get(0).unwrap();
+    Some((variant, format!("{}{}", &name[..whole.start()], &name[whole.end()..])))
+}
+
+/// Puts each item into the set that `find` returns for its name, renamed to
+/// the name `find` returns with it. Items without a set are dropped.
+fn split_by<T, F>(vec: Vec<T>, len: usize, name: fn(&mut T) -> &mut String, find: F) -> Vec<Vec<T>>
+    where F: Fn(&str) -> Option<(usize, String)>
+{
+    let mut sets: Vec<Vec<T>> = (0..len).map(|_| Vec::new()).collect();
+    for mut item in vec {
+        if let Some((i, key)) = find(name(&mut item)) {
+            *name(&mut item) = key;
+            sets[i].push(item);
+        }
+    }
+    sets
+}
+
+/// Returns the index of the first prefix `name` starts with, and `name`
+/// without it.
+fn prefix_of<S: AsRef<str>>(name: &str, prefixes: &[S]) -> Option<(usize, String)> {
+    let i = prefixes.iter().position(|p| name.starts_with(p.as_ref()))?;
+    Some((i, name[prefixes[i].as_ref().len()..].to_string()))
+}
+
+/// Returns the index of the variant of `name` in `variants`, and the key
+/// it's paired by.
+fn variant_index<S: AsRef<str>>(name: &str, re: &Regex, variants: &[S]) -> Option<(usize, String)> {
+    let (variant, key) = variant_of(name, re)?;
+    let i = variants.iter().position(|v| v.as_ref() == variant)?;
+    Some((i, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::Arbitrary;
+    use quickcheck::Gen;
+    use rand::Rng;
+    use rand::distributions::Alphanumeric;
+    use std::iter;
+
+    #[derive(Clone, Debug)]
+    struct AlphaString(String);
+
+    impl Arbitrary for AlphaString {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            let size = g.size();
+            let size = g.gen_range(1, size);
+            AlphaString(iter::repeat(()).map(|()| g.sample(Alphanumeric)).take(size).collect())
+        }
+    }
+
+    mod split_benchmarks {
+        use super::super::split_benchmarks;
+        use super::AlphaString;
+        use benchmark::Benchmark;
+
+        quickcheck! {
+            fn from_original(benches: Vec<Benchmark>, old: AlphaString, new: AlphaString) -> bool {
+                let AlphaString(old) = old;
+                let AlphaString(new) = new;
+                let result = split_benchmarks(benches.clone(), &old, &new);
+
+                result.0.into_iter().all(|mut b| {
+                    b.name = old.clone() + &b.name;
+                    benches.contains(&b)
+                }) &&
+                result.1.into_iter().all(|mut b| {
+                    b.name = new.clone() + &b.name;
+                    benches.contains(&b)
+                })
+            }
+
+            fn non_overlapping(benches: Vec<Benchmark>,
+                               old: AlphaString,
+                               new: AlphaString)
+                               -> bool {
+                let AlphaString(old) = old;
+                let AlphaString(new) = new;
+                let result = split_benchmarks(benches.clone(), &old, &new);
+                let mut benches = benches;
+
+                let results: Vec<Benchmark> = result.0
+                    .into_iter()
+                    .map(|mut b| {
+                        b.name = old.clone() + &b.name;
+                        b
+                    })
+                    .chain(result.1.into_iter().map(|mut b| {
+                        b.name = new.clone() + &b.name;
+                        b
+                    }))
+                    .collect();
+
+                for result in results {
+                    if let Some(index) = benches.iter().position(|b| b == &result) {
+                        benches.swap_remove(index);
+                    } else {
+                        return false;
+                    }
+                }
+
+                true
+            }
+
+            fn dropped_non_prefix(benches: Vec<Benchmark>,
+                                  old: AlphaString,
+                                  new: AlphaString)
+                                  -> bool {
+                let AlphaString(old) = old;
+                let AlphaString(new) = new;
+                let result = split_benchmarks(benches.clone(), &old, &new);
+                let mut benches = benches;
+
+                let results: Vec<Benchmark> = result.0
+                    .into_iter()
+                    .map(|mut b| {
+                        b.name = old.clone() + &b.name;
+                        b
+                    })
+                    .chain(result.1.into_iter().map(|mut b| {
+                        b.name = new.clone() + &b.name;
+                        b
+                    }))
+                    .collect();
+
+                for result in results {
+                    if let Some(index) = benches.iter().position(|b| b == &result) {
+                        benches.swap_remove(index);
+                    }
+                }
+
+                benches.into_iter().all(|b| !(b.name.starts_with(&old) || b.name.starts_with(&new)))
+            }
+        }
+    }
+    mod split_prefixes {
+        use super::super::{split_failures, split_prefixes};
+        use benchmark::{bench, Failure, Status};
+
+        #[test]
+        fn first_prefix_wins() {
+            let benches = vec![bench("v1::a", 1), bench("v2::a", 2), bench("v1::b", 3), bench("other", 4)];
+            let sets = split_prefixes(benches, &["v1::", "v", "v3::"]);
+            let names: Vec<Vec<&str>> = sets.iter()
+                .map(|set| set.iter().map(|b| &*b.name).collect())
+                .collect();
+            assert_eq!(names, vec![vec!["a", "b"], vec!["2::a"], vec![]]);
+        }
+
+        #[test]
+        fn failures() {
+            let failures = vec![Failure::new("v1::a", Status::Failed),
+                                Failure::new("v2::a", Status::Ignored),
+                                Failure::new("other", Status::Failed)];
+            let sets = split_failures(failures, &["v1::", "v2::"]);
+            assert_eq!(sets, vec![vec![Failure::new("a", Status::Failed)],
+                                  vec![Failure::new("a", Status::Ignored)]]);
+        }
+    }
+    mod split_variants {
+        use super::super::{split_failure_variants, split_variants, variant_of};
+        use benchmark::{bench, Failure, Status};
+        use regex::Regex;
+
+        #[test]
+        fn suffix() {
+            let re = Regex::new(r"/(?P<variant>\w+)$").unwrap();
+            let benches = vec![bench("parse_json/scalar", 1),
+                               bench("parse_json/simd", 2),
+                               bench("parse_csv/avx", 3),
+                               bench("render", 4)];
+            let sets = split_variants(benches, &re, &["scalar", "simd"]);
+            let names: Vec<Vec<&str>> = sets.iter()
+                .map(|set| set.iter().map(|b| &*b.name).collect())
+                .collect();
+            assert_eq!(names, vec![vec!["parse_json"], vec!["parse_json"]]);
+        }
+
+        #[test]
+        fn infix() {
+            let re = Regex::new(r"::(?P<variant>v\d+)::").unwrap();
+            assert_eq!(variant_of("json::v2::parse", &re), Some(("v2", "jsonparse".to_string())));
+            let re = Regex::new(r"(?P<variant>v\d+)::").unwrap();
+            assert_eq!(variant_of("json::v2::parse", &re), Some(("v2", "json::parse".to_string())));
+            assert_eq!(variant_of("json::parse", &re), None);
+        }
+
+        #[test]
+        fn failures() {
+            let re = Regex::new(r"/(?P<variant>\w+)$").unwrap();
+            let failures = vec![Failure::new("parse_json/simd", Status::Failed),
+                                Failure::new("parse_json/avx", Status::Failed)];
+            let sets = split_failure_variants(failures, &re, &["scalar", "simd"]);
+            assert_eq!(sets, vec![vec![], vec![Failure::new("parse_json", Status::Failed)]]);
+        }
+    }
+}
@@ -1,9 +1,10 @@
+extern crate benchcmp;
 extern crate docopt;
-#[macro_use]
-extern crate lazy_static;
+extern crate flate2;
+#[cfg(feature = "tui")]
+extern crate ratatui;
 extern crate regex;
-#[macro_use]
-extern crate prettytable;
+extern crate ruzstd;
 #[macro_use]
 extern crate serde_derive;
 #[cfg(test)]
@@ -12,20 +13,22 @@ extern crate quickcheck;
 #[cfg(test)]
 extern crate rand;
 
-use std::io::{self, BufRead};
+use regex::Regex;
+use std::env;
+use std::io::{self, BufRead, BufReader, IsTerminal, Read};
 use std::fs::File;
+use std::mem;
 use std::path::{Path, PathBuf};
 use std::process;
 
 use docopt::Docopt;
-use prettytable::Table;
-use prettytable::format;
-
-use benchmark::{Benchmarks, Benchmark};
-use error::{Result, Error};
-
-mod benchmark;
-mod error;
+use flate2::read::MultiGzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+use ruzstd::decoding::errors::{FrameDecoderError, ReadFrameHeaderError};
+use benchcmp::{criterion, Benchmark, Benchmarks, Duplicates, Error, Failure, PairedBenchmarks, Parsed, Qualifier,
+               Registry, Result, Status};
+use benchcmp::report::{self, Context, Reporter};
+use benchcmp::scaling;
 
 macro_rules! eprintln {
     ($($tt:tt)*) => {{
@@ -34,12 +37,16 @@ macro_rules! eprintln {
     }}
 }
 
-const USAGE: &'static str = r#"
+#[cfg(feature = "tui")]
+mod tui;
+
+const USAGE: &str = r#"
 Compares Rust micro-benchmark results.
 
 Usage:
     cargo benchcmp [options] <old> <new>
-    cargo benchcmp [options] <old> <new> <file>
+    cargo benchcmp [options] <old> <new> <file> [<file2>]
+    cargo benchcmp [options] --prefixes <prefixes> <file> [<file2>]
     cargo benchcmp -h | --help
     cargo benchcmp --version
 
@@ -48,11 +55,30 @@ The first version takes two files and compares the common benchmarks.
 The second version takes two benchmark name prefixes and one benchmark output
 file, and compares the common benchmarks (as determined by comparing the
 benchmark names with their prefixes stripped). Benchmarks not matching either
-prefix are ignored completely.
+prefix are ignored completely. If a second file is given, benchmarks with the
+<old> prefix are read from the first file and those with the <new> prefix
+from the second.
+
+The third version is like the second, but takes any number of prefixes,
+separated by commas, e.g., v1::,v2::,v3::. The first prefix is the baseline
+that the others are compared against, and each of them becomes a column of
+the table (the only format that supports more than two prefixes). With a
+second file, the baseline is read from the first file and the others from the
+second.
 
 If benchmark output is sent on stdin, then the second version is used and the
 third file parameter is not needed.
 
+Benchmark output compressed with gzip or zstd (e.g., old.txt.gz) is
+decompressed transparently, whether it is read from a file or from stdin.
+
+Results saved by Criterion.rs are compared by passing directories instead of
+files. In the first version, <old> and <new> are two Criterion output
+directories (e.g., target/criterion), and the latest run in each is compared.
+In the second version, <file> is a Criterion output directory and <old> and
+<new> are the names of two baselines saved in it with --save-baseline (use
+new for the latest run).
+
 Options:
     -h, --help           Show this help message and exit.
     --version            Show the version.
@@ -61,9 +87,94 @@ Options:
     --threshold <n>      Show only comparisons with a percentage change greater
                          than this threshold.
     --variance           Show the variance of each benchmark.
+    --bars               Show a bar for each comparison, proportional to its
+                         percent change and centred on zero.
     --improvements       Show only improvements.
     --regressions        Show only regressions.
     --color <when>       Show colored rows: never, always or auto [default: auto]
+    --format <fmt>       Output format: table, html, svg, junit, github or tap.
+                         The html format is a self-contained report with a
+                         sortable table, summary statistics and a chart of the
+                         changes. The svg format is a bar chart of the changes.
+                         The junit format reports each comparison as a test
+                         case, which fails if it regressed by at least the
+                         threshold (or at all, if no threshold is given).
+                         The github format emits a GitHub Actions warning for
+                         each regression, or an error if it would fail in
+                         junit, and appends a Markdown summary to the file in
+                         $GITHUB_STEP_SUMMARY if it is set. The tap format
+                         emits one test point per comparison, which fails
+                         under the same condition as in junit.
+                         [default: table]
+    --input-format <name>
+                         The format of the benchmark output: libtest (the
+                         text printed by cargo bench), libtest-json (printed
+                         with -Z unstable-options --format json), divan, iai,
+                         hyperfine (the file written by --export-json) or
+                         auto to detect it. [default: auto]
+    --metric <name>      Compare the named metric instead of the time, e.g.,
+                         throughput, or instructions, l1_accesses or
+                         estimated_cycles for iai output (which is compared
+                         by estimated_cycles by default).
+    --strict             Fail if a line looks like the result of a benchmark,
+                         but can't be parsed, instead of skipping it.
+    --metrics <names>    Show these metrics in the table instead of the compared
+                         one, separated by commas, e.g., time,throughput,mean.
+                         Each gets an old, a new and a percent change column.
+    --qualify <by>       Prefix the name of each benchmark with its bench target
+                         or crate, e.g., my_crate::bench, so that benchmarks
+                         with the same name in different targets or crates
+                         are told apart and grouped together. <by> is target
+                         or crate. Both are read from the `Running` lines
+                         cargo prints before each binary's results.
+    --crate <names>      Only compare benchmarks from these crates, separated
+                         by commas. A bench target belongs to the crate of
+                         the last `Running unittests` line before it, so the
+                         targets of a crate without a library or binary may
+                         be attributed to the crate before it.
+    --duplicates <policy>
+                         What to do with benchmarks that share a name within
+                         one input: warn to keep the first with a warning,
+                         error, first, last, min (the best), median, or
+                         target to prefix them with the bench target from
+                         the preceding `Running` line, e.g., foo::bench, and
+                         with its crate too if that is ambiguous.
+                         [default: warn]
+    --prefixes <prefixes>
+                         The benchmark name prefixes to compare in the third
+                         version, separated by commas. The first one is the
+                         baseline.
+    --split <regex>      Find the variant of each benchmark with this regex
+                         instead of a prefix, in the second and third
+                         versions. Its capture group named variant is
+                         matched against <old> and <new> (or the names
+                         given to --prefixes), and the rest of the name,
+                         with the whole match removed, pairs the variants
+                         up. E.g., /(?P<variant>\w+)$ compares
+                         parse_json/simd to parse_json/scalar.
+    --scaling <regex>    Group benchmarks that only differ by a numeric
+                         parameter, e.g., sort_10 and sort_1000, into
+                         families and show how each family scales with it.
+                         The regex finds the parameter with a capture group
+                         named param, e.g., _(?P<param>\d+)$, and the rest of
+                         the name, with the whole match removed, names the
+                         family. Each family ends with a fit row, which
+                         estimates the exponent k of param^k in the old and
+                         new results and in their ratio. A positive ratio
+                         exponent means that the change is relatively
+                         better for small parameters than for large ones.
+    --tui                Explore the comparisons in an interactive terminal
+                         UI instead of printing a report. Type / to filter
+                         them by name, 1 to 6 to sort them by a column (again
+                         to reverse), v and t to show or hide the variance and
+                         throughput columns, and q to quit. The selected
+                         benchmark's measurements and metrics are shown below
+                         the table.
+    --normalize-by <name>
+                         Divide the results of each set by that set's result
+                         for the named benchmark before comparing. This
+                         factors out differences in machine speed when the
+                         old and new results come from different hosts.
 "#;
 
 #[derive(Debug, Deserialize)]
@@ -71,12 +182,33 @@ struct Args {
     arg_old: String,
     arg_new: String,
     arg_file: Option<String>,
+    arg_file2: Option<String>,
+    flag_prefixes: Option<String>,
+    flag_split: Option<String>,
+    /// The compiled regex of `--split`.
+    #[serde(skip)]
+    split: Option<Regex>,
+    flag_scaling: Option<String>,
+    flag_tui: bool,
+    /// The compiled regex of `--scaling`.
+    #[serde(skip)]
+    scaling: Option<Regex>,
     flag_threshold: Option<u8>,
     flag_include_missing: bool,
     flag_variance: bool,
     flag_improvements: bool,
     flag_regressions: bool,
     flag_color: When,
+    flag_normalize_by: Option<String>,
+    flag_format: Format,
+    flag_bars: bool,
+    flag_input_format: String,
+    flag_metric: Option<String>,
+    flag_metrics: Option<String>,
+    flag_strict: bool,
+    flag_duplicates: DuplicatesFlag,
+    flag_qualify: Option<QualifyFlag>,
+    flag_crate: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -86,10 +218,89 @@ enum When {
     Auto,
 }
 
+#[derive(Debug, Deserialize)]
+enum QualifyFlag {
+    Target,
+    Crate,
+}
+
+#[derive(Debug, Deserialize)]
+enum DuplicatesFlag {
+    Warn,
+    Error,
+    First,
+    Last,
+    Min,
+    Median,
+    Target,
+}
+
+#[derive(Debug, Deserialize)]
+enum Format {
+    Table,
+    Html,
+    Svg,
+    Junit,
+    Github,
+    Tap,
+}
+
+/// Compiles the regex given to `option`, or exits if it is invalid or has no
+/// capture group named `group`.
+fn regex_with_group(pattern: &str, group: &str, option: &str) -> Regex {
+    let re = Regex::new(pattern).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    if !re.capture_names().any(|name| name == Some(group)) {
+        eprintln!("the regex of {} has no capture group named {}", option, group);
+        process::exit(1);
+    }
+    re
+}
+
+/// The benchmarks with results and the failures of one set.
+type Set = (Vec<Benchmark>, Vec<Failure>);
+
 fn main() {
-    let args: Args = Docopt::new(USAGE)
+    let mut args: Args = Docopt::new(USAGE)
         .and_then(|d| d.version(Some(version())).deserialize())
         .unwrap_or_else(|e| e.exit());
+    if args.flag_prefixes.is_some() && args.arg_file.is_none() {
+        // Docopt matches `--prefixes <prefixes> <file> <file2>` with the
+        // first usage pattern, so the files end up in <old> and <new>.
+        args.arg_file = Some(mem::take(&mut args.arg_old));
+        args.arg_file2 = Some(mem::take(&mut args.arg_new));
+    }
+    args.split = args.flag_split.as_ref().map(|p| regex_with_group(p, "variant", "--split"));
+    args.scaling = args.flag_scaling.as_ref().map(|p| regex_with_group(p, "param", "--scaling"));
+    let prefixes = args.prefixes();
+    if prefixes.len() < 2 {
+        eprintln!("at least two prefixes are needed to compare benchmarks");
+        process::exit(1);
+    }
+    if prefixes.len() > 2 && !matches!(args.flag_format, Format::Table) {
+        eprintln!("only the table format can compare more than two prefixes");
+        process::exit(1);
+    }
+    if args.scaling.is_some() && (prefixes.len() > 2 || !matches!(args.flag_format, Format::Table)) {
+        eprintln!("--scaling only supports two sets of benchmarks and the table format");
+        process::exit(1);
+    }
+    if args.flag_tui {
+        if !cfg!(feature = "tui") {
+            eprintln!("--tui isn't available, since cargo-benchcmp was built without the tui feature");
+            process::exit(1);
+        }
+        if prefixes.len() > 2 || args.scaling.is_some() {
+            eprintln!("--tui only supports two sets of benchmarks without --scaling");
+            process::exit(1);
+        }
+        if !io::stdout().is_terminal() {
+            eprintln!("--tui needs a terminal");
+            process::exit(1);
+        }
+    }
     if let Err(e) = args.run() {
         eprintln!("{}", e);
         process::exit(1);
@@ -98,175 +309,315 @@ fn main() {
 
 impl Args {
     fn run(&self) -> Result<()> {
-        let (name_old, name_new) = Args::names(&self.arg_old, &self.arg_new);
-        let benches = try!(self.parse_benchmarks()).paired();
-        if benches.comparisons().len() > 0 {
-            let mut output = Table::new();
-            output.set_format(*format::consts::FORMAT_CLEAN);
-            output.add_row(row![
-                b->"name",
-                b->format!("{} ns/iter", name_old),
-                b->format!("{} ns/iter", name_new),
-                br->"diff ns/iter",
-                br->"diff %",
-                br->"speedup"
-            ]);
-            for c in benches.comparisons() {
-                let abs_per = (c.diff_ratio * 100f64).abs().trunc() as u8;
-                let regression = c.diff_ns > 0;
-                if self.flag_threshold.map_or(false, |t| abs_per < t) ||
-                   self.flag_regressions && !regression ||
-                   self.flag_improvements && regression {
-                    continue;
-                }
-                output.add_row(c.to_row(self.flag_variance, regression));
+        let mut sets = vec![];
+        for mut benches in self.parse_benchmarks()? {
+            self.prepare(&mut benches)?;
+            sets.push(benches.paired());
+        }
+        let mut ctx = self.context();
+        if let Some(unit) = sets[0].unit() {
+            ctx.unit = unit.to_string();
+        }
+        let stdout = io::stdout();
+        if sets.len() > 1 {
+            let named: Vec<(String, PairedBenchmarks)> =
+                self.prefixes().into_iter().skip(1).zip(sets).collect();
+            self.table().report_sets(&named, &ctx, &mut stdout.lock())?;
+            for (name, benches) in &named {
+                self.print_unpaired(benches, &ctx.name_old, name);
             }
+            return Ok(());
+        }
+        let benches = sets.pop().unwrap();
+        #[cfg(feature = "tui")]
+        {
+            if self.flag_tui {
+                tui::run(&benches, &ctx)?;
+                self.print_unpaired(&benches, "old", "new");
+                return Ok(());
+            }
+        }
+        if let Some(ref re) = self.scaling {
+            let families = scaling::families(&benches, re);
+            if families.is_empty() {
+                eprintln!("WARNING: no benchmarks matched the regex of --scaling");
+            }
+            self.table().report_scaling(&families, &ctx, &mut stdout.lock())?;
+            self.print_unpaired(&benches, "old", "new");
+            return Ok(());
+        }
+        let reporter: Box<dyn Reporter> = match self.flag_format {
+            Format::Table => Box::new(self.table()),
+            Format::Html => Box::new(report::Html),
+            Format::Svg => Box::new(report::Svg),
+            Format::Junit => Box::new(report::Junit),
+            Format::Github => {
+                let mut github = report::Github::new();
+                github.summary = env::var_os("GITHUB_STEP_SUMMARY")
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from);
+                Box::new(github)
+            }
+            Format::Tap => Box::new(report::Tap),
+        };
+        reporter.report(&benches, &ctx, &mut stdout.lock())?;
+        if let Format::Table = self.flag_format {
+            self.print_notes(&benches, &ctx);
+        }
+        Ok(())
+    }
 
-            if self.flag_include_missing {
-                for b in benches.missing_old() {
-                    output.add_row(row![b.name, b.fmt_ns(self.flag_variance), "n/a", r->"n/a", r->"n/a"]);
-                }
-
-                for b in benches.missing_new() {
-                    output.add_row(row![b.name, "n/a", b.fmt_ns(self.flag_variance), r->"n/a", r->"n/a"]);
-                }
+    /// Applies the user's options that transform the benchmarks before they
+    /// are paired.
+    fn prepare(&self, benches: &mut Benchmarks) -> Result<()> {
+        if let Some(ref names) = self.flag_crate {
+            let crates: Vec<String> = names.split(',').map(|n| n.trim().to_string()).collect();
+            benches.retain_crates(&crates);
+        }
+        match self.flag_qualify {
+            Some(QualifyFlag::Target) => benches.qualify(Qualifier::Target),
+            Some(QualifyFlag::Crate) => benches.qualify(Qualifier::Crate),
+            None => {}
+        }
+        if let Some(ref name) = self.flag_metric {
+            benches.select_metric(name)?;
+        }
+        if let DuplicatesFlag::Warn = self.flag_duplicates {
+            for dup in benches.duplicates() {
+                eprintln!("WARNING: {}, keeping the first", dup);
             }
+        }
+        benches.dedup(match self.flag_duplicates {
+            DuplicatesFlag::Warn | DuplicatesFlag::First => Duplicates::First,
+            DuplicatesFlag::Error => Duplicates::Error,
+            DuplicatesFlag::Last => Duplicates::Last,
+            DuplicatesFlag::Min => Duplicates::Min,
+            DuplicatesFlag::Median => Duplicates::Median,
+            DuplicatesFlag::Target => Duplicates::Target,
+        })?;
+        if let Some(ref name) = self.flag_normalize_by {
+            benches.normalize_by(name)?;
+        }
+        Ok(())
+    }
 
-            if output.len() > 1 {
-                match self.flag_color {
-                    When::Auto => output.printstd(),
-                    When::Never => {
-                        try!(output.print(&mut io::stdout()));
-                    }
-                    When::Always => {
-                        try!(output.print_tty(true));
-                    }
-                };
-            } else {
-                let comparisions = benches.comparisons().len();
-                let improvements = benches.comparisons().iter().filter(|c| c.diff_ns <= 0).count();
-                let regressions = comparisions - improvements;
-
-                match (self.flag_threshold, self.flag_improvements, self.flag_regressions) {
-                    (Some(threshold), false, false) => {
-                        eprintln!(
-                            "All ({}) benchmarks are within a {}% threshold",
-                            comparisions,
-                            threshold,
-                        )
-                    }
-                    (Some(threshold), true, false) if improvements > 0 => {
-                        eprintln!(
-                            "All ({}/{}) improvements are within a {}% threshold",
-                            improvements,
-                            comparisions,
-                            threshold,
-                        )
-                    }
-                    (_, true, false) => {
-                        eprintln!("{}/{} benchmarks improved", improvements, comparisions)
-                    }
-                    (Some(threshold), false, true) if regressions > 0 => {
-                        eprintln!(
-                            "All ({}/{}) regressions are within a {}% threshold",
-                            regressions,
-                            comparisions,
-                            threshold,
-                        )
-                    }
-                    (_, false, true) => {
-                        eprintln!("{}/{} benchmarks regressed", regressions, comparisions)
-                    }
-                    _ => eprintln!("WARNING: nothing to output"),
+    /// Builds the table reporter from the user's options.
+    fn table(&self) -> report::Table {
+        let mut table = report::Table::new();
+        table.color = match self.flag_color {
+            When::Never => false,
+            When::Always => true,
+            When::Auto => io::stdout().is_terminal(),
+        };
+        table.bars = self.flag_bars;
+        table
+    }
+
+    /// Returns the prefixes to split benchmarks with, or the names of the
+    /// two files or Criterion baselines to compare.
+    fn prefixes(&self) -> Vec<String> {
+        match self.flag_prefixes {
+            Some(ref prefixes) => prefixes.split(',').map(|p| p.to_string()).collect(),
+            None => vec![self.arg_old.clone(), self.arg_new.clone()],
+        }
+    }
+
+    /// Builds the report context from the user's options.
+    fn context(&self) -> Context {
+        let prefixes = self.prefixes();
+        let (name_old, name_new) = Args::names(&prefixes[0], &prefixes[1]);
+        let mut ctx = Context::new(&name_old, &name_new);
+        ctx.threshold = self.flag_threshold;
+        ctx.only_improvements = self.flag_improvements;
+        ctx.only_regressions = self.flag_regressions;
+        ctx.variance = self.flag_variance;
+        ctx.include_missing = self.flag_include_missing;
+        if let Some(ref names) = self.flag_metrics {
+            ctx.metrics = names.split(',').map(|n| n.trim().to_string()).collect();
+        }
+        ctx
+    }
+
+    /// Prints notes about the table on stderr: why it is empty, if it is,
+    /// and which benchmarks couldn't be paired.
+    fn print_notes(&self, benches: &PairedBenchmarks, ctx: &Context) {
+        let missing = !benches.missing_old().is_empty() || !benches.missing_new().is_empty();
+        let empty = ctx.shown(benches).is_empty() && !(ctx.include_missing && missing);
+        if !benches.comparisons().is_empty() && empty {
+            let comparisions = benches.comparisons().len();
+            let improvements = benches.comparisons().iter().filter(|c| !c.is_regression()).count();
+            let regressions = comparisions - improvements;
+
+            match (self.flag_threshold, self.flag_improvements, self.flag_regressions) {
+                (Some(threshold), false, false) => {
+                    eprintln!(
+                        "All ({}) benchmarks are within a {}% threshold",
+                        comparisions,
+                        threshold,
+                    )
+                }
+                (Some(threshold), true, false) if improvements > 0 => {
+                    eprintln!(
+                        "All ({}/{}) improvements are within a {}% threshold",
+                        improvements,
+                        comparisions,
+                        threshold,
+                    )
+                }
+                (_, true, false) => {
+                    eprintln!("{}/{} benchmarks improved", improvements, comparisions)
+                }
+                (Some(threshold), false, true) if regressions > 0 => {
+                    eprintln!(
+                        "All ({}/{}) regressions are within a {}% threshold",
+                        regressions,
+                        comparisions,
+                        threshold,
+                    )
                 }
+                (_, false, true) => {
+                    eprintln!("{}/{} benchmarks regressed", regressions, comparisions)
+                }
+                _ => eprintln!("WARNING: nothing to output"),
             }
         }
 
-        // If there were any unpaired benchmarks, show them now.
-        if !self.flag_include_missing && !benches.missing_old().is_empty() {
-            let missed = benches.missing_old()
-                .iter()
+        self.print_unpaired(benches, "old", "new");
+    }
+
+    /// Prints the benchmarks that couldn't be paired, or that failed, on
+    /// stderr. `old` and `new` name the two sets.
+    fn print_unpaired(&self, benches: &PairedBenchmarks, old: &str, new: &str) {
+        let lacking = benches.missing_metric();
+        let unpaired = |missing: &[Benchmark]| {
+            missing.iter()
                 .map(|b| b.name.to_string())
+                .filter(|name| !lacking.contains(name))
                 .collect::<Vec<String>>()
-                .join(", ");
-            eprintln!("WARNING: benchmarks in old but not in new: {}", missed);
+        };
+        let missing_old = unpaired(benches.missing_old());
+        let missing_new = unpaired(benches.missing_new());
+        if !self.flag_include_missing && !missing_old.is_empty() {
+            eprintln!("WARNING: benchmarks in {} but not in {}: {}", old, new, missing_old.join(", "));
         }
-        if !self.flag_include_missing && !benches.missing_new().is_empty() {
-            let missed = benches.missing_new()
-                .iter()
-                .map(|b| b.name.to_string())
-                .collect::<Vec<String>>()
-                .join(", ");
-            eprintln!("WARNING: benchmarks in new but not in old: {}", missed);
+        if !self.flag_include_missing && !missing_new.is_empty() {
+            eprintln!("WARNING: benchmarks in {} but not in {}: {}", new, old, missing_new.join(", "));
         }
-        Ok(())
+        if !self.flag_include_missing && !lacking.is_empty() {
+            eprintln!("WARNING: benchmarks in both {} and {}, but without a {} metric in one: {}",
+                      old, new, benches.metric(), lacking.join(", "));
+        }
+        print_failures(benches.failures_old(), old);
+        print_failures(benches.failures_new(), new);
     }
 
     /// Parse benchmarks from the command line invocation given.
-    fn parse_benchmarks(&self) -> Result<Benchmarks> {
-        if let Some(ref one_file) = self.arg_file {
-            if one_file == "-" {
-                let stdin = io::stdin();
-                let stdin_lock = stdin.lock();
-                let benches = try!(Args::parse_buffer(stdin_lock));
-                Ok(Benchmarks::from(Args::split_benchmarks(benches, &self.arg_old, &self.arg_new)))
-            } else {
-                self.parse_file_benchmarks(one_file)
-            }
-        } else {
-            self.parse_old_new_benchmarks()
+    ///
+    /// Returns one comparable set per prefix after the first, each of them
+    /// against the benchmarks with the first prefix.
+    fn parse_benchmarks(&self) -> Result<Vec<Benchmarks>> {
+        match self.arg_file {
+            Some(ref file) => self.parse_prefixed_benchmarks(file),
+            None => self.parse_old_new_benchmarks().map(|benches| vec![benches]),
         }
     }
 
     /// Parses benchmarks from two files: one containing old benchmark output
-    /// and another containing new benchmark output.
+    /// and another containing new benchmark output. If both are Criterion
+    /// output directories, their latest runs are read instead.
     fn parse_old_new_benchmarks(&self) -> Result<Benchmarks> {
-        let b_old = try!(Args::parse_buffer(io::BufReader::new(try!(open_file(&self.arg_old)))));
-        let b_new = try!(Args::parse_buffer(io::BufReader::new(try!(open_file(&self.arg_new)))));
+        if Path::new(&self.arg_old).is_dir() && Path::new(&self.arg_new).is_dir() {
+            let b_old = criterion::read_dir(&self.arg_old, "new")?;
+            let b_new = criterion::read_dir(&self.arg_new, "new")?;
+            return Ok(Benchmarks::from((b_old, b_new)));
+        }
+        let p_old = self.parse_reader(open_file(&self.arg_old)?, &self.arg_old)?;
+        let p_new = self.parse_reader(open_file(&self.arg_new)?, &self.arg_new)?;
 
-        Ok(Benchmarks::from((b_old, b_new)))
+        let mut benches = Benchmarks::from((p_old.benchmarks, p_new.benchmarks));
+        benches.add_failures(p_old.failures, p_new.failures);
+        Ok(benches)
     }
 
-    /// Parses benchmarks from one file, then splits on the two prefixes.
-    /// See also: Args::split_benchmarks
-    fn parse_file_benchmarks<P>(&self, file: P) -> Result<Benchmarks>
-        where P: AsRef<Path>
-    {
-        let benches = try!(Args::parse_buffer(io::BufReader::new(try!(File::open(file)))));
-        Ok(Benchmarks::from(Args::split_benchmarks(benches, &self.arg_old, &self.arg_new)))
+    /// Parses benchmarks in prefix mode, with one set per prefix.
+    ///
+    /// Without a second file, all sets are split from `file`. Otherwise the
+    /// first set is split from `file` and the others from the second file.
+    fn parse_prefixed_benchmarks(&self, file: &str) -> Result<Vec<Benchmarks>> {
+        let prefixes = self.prefixes();
+        let mut sets = match self.arg_file2 {
+            None => self.read_sets(file, &prefixes)?,
+            Some(ref file2) => {
+                let mut sets = self.read_sets(file, &prefixes[..1])?;
+                sets.extend(self.read_sets(file2, &prefixes[1..])?);
+                sets
+            }
+        };
+        let (base, base_failures) = sets.remove(0);
+        Ok(sets.into_iter()
+            .map(|(benches, failures)| {
+                let mut paired = Benchmarks::from((base.clone(), benches));
+                paired.add_failures(base_failures.clone(), failures);
+                paired
+            })
+            .collect())
     }
 
-    /// Parse benchmarks from a buffered reader.
-    fn parse_buffer<B: BufRead>(buffer: B) -> Result<Vec<Benchmark>> {
-        let iter = buffer.lines();
-        let mut vec = Vec::with_capacity(iter.size_hint().0);
-        for result in iter {
-            if let Ok(bench) = try!(result).parse() {
-                vec.push(bench)
-            }
+    /// Reads the benchmarks and failures for each prefix from `source`.
+    ///
+    /// `source` is a file, `-` for stdin, or a Criterion output directory,
+    /// in which case the prefixes name its baselines. With `--split`, the
+    /// prefixes are the variants found by its regex instead.
+    fn read_sets(&self, source: &str, prefixes: &[String]) -> Result<Vec<Set>> {
+        if Path::new(source).is_dir() {
+            return prefixes.iter()
+                .map(|baseline| Ok((criterion::read_dir(source, baseline)?, vec![])))
+                .collect();
         }
-        Ok(vec)
+        let parsed = if source == "-" {
+            self.parse_reader(decompress(io::stdin().lock())?, "<stdin>")?
+        } else {
+            self.parse_reader(open_file(source)?, source)?
+        };
+        let (benches, failures) = match self.split {
+            Some(ref re) => (benchcmp::split_variants(parsed.benchmarks, re, prefixes),
+                             benchcmp::split_failure_variants(parsed.failures, re, prefixes)),
+            None => (benchcmp::split_prefixes(parsed.benchmarks, prefixes),
+                     benchcmp::split_failures(parsed.failures, prefixes)),
+        };
+        Ok(benches.into_iter().zip(failures).collect())
     }
 
-    /// Splits benchmarks from one source with two prefixes. The first prefix
-    /// identifies benchmarks in the old set and the second prefix identifies
-    /// benchmarks in the new set where all benchmarks are found in one file.
-    fn split_benchmarks(vec: Vec<Benchmark>,
-                        arg_old: &str,
-                        arg_new: &str)
-                        -> (Vec<Benchmark>, Vec<Benchmark>) {
-        let mut b_old = Vec::new();
-        let mut b_new = Vec::new();
-        for mut bench in vec {
-            if bench.name.starts_with(arg_old) {
-                bench.name = bench.name[arg_old.len()..].to_string();
-                b_old.push(bench);
-            } else if bench.name.starts_with(arg_new) {
-                bench.name = bench.name[arg_new.len()..].to_string();
-                b_new.push(bench);
+    /// Parses benchmarks from a reader, in the format selected with
+    /// `--input-format`.
+    ///
+    /// `input` describes the reader in errors and warnings. With `--strict`,
+    /// malformed benchmark results are an error. Otherwise they are skipped,
+    /// but a warning is printed if no benchmarks could be parsed and none
+    /// failed.
+    fn parse_reader<R: io::Read>(&self, rdr: R, input: &str) -> Result<Parsed> {
+        let parsed = Registry::new().parse_with_diagnostics(&self.flag_input_format, rdr)?;
+        let errs = &parsed.errors;
+        if let (true, Some(err)) = (self.flag_strict, errs.first()) {
+            return Err(Error::Parse {
+                input: input.to_string(),
+                err: err.clone(),
+            });
+        }
+        let failed = parsed.failures.iter().any(|f| f.status == Status::Failed);
+        if parsed.benchmarks.is_empty() && !failed {
+            match errs.first() {
+                None => eprintln!("WARNING: no benchmarks found in {}", input),
+                Some(err) => {
+                    eprintln!("WARNING: no benchmarks found in {}, but {} malformed results were \
+                               skipped (first at {})",
+                              input,
+                              errs.len(),
+                              err)
+                }
             }
         }
-        (b_old, b_new)
+        Ok(parsed)
     }
 
     /// Returns the names that should be used in the column header.
@@ -311,6 +662,14 @@ impl Args {
     }
 }
 
+/// Prints a warning for every benchmark in the set `name` that failed or
+/// was ignored.
+fn print_failures(failures: &[Failure], name: &str) {
+    for failure in failures {
+        eprintln!("WARNING: {}", failure.describe(name));
+    }
+}
+
 fn version() -> String {
     let (maj, min, pat) = (option_env!("CARGO_PKG_VERSION_MAJOR"),
                            option_env!("CARGO_PKG_VERSION_MINOR"),
@@ -322,14 +681,55 @@ fn version() -> String {
 }
 
 /// `open_file` is like `File::open`, except it gives a better error message
-/// when it fails (i.e., it includes the file path).
-fn open_file<P: AsRef<Path>>(path: P) -> Result<File> {
-    File::open(&path).map_err(|err| {
-        Error::OpenFile {
-            path: path.as_ref().to_path_buf(),
-            err: err,
-        }
-    })
+/// when it fails (i.e., it includes the file path), and it decompresses the
+/// file if it is compressed (see `decompress`).
+fn open_file<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read>> {
+    File::open(&path)
+        .and_then(|file| decompress(BufReader::new(file)))
+        .map_err(|err| {
+            Error::OpenFile {
+                path: path.as_ref().to_path_buf(),
+                err,
+            }
+        })
+}
+
+/// The first bytes of a gzip stream.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+/// The first bytes of a zstd frame.
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Returns a reader of the decompressed contents of `rdr` if it starts like
+/// gzip or zstd data, and `rdr` itself otherwise.
+fn decompress<R: BufRead + 'static>(mut rdr: R) -> io::Result<Box<dyn Read>> {
+    let magic = rdr.fill_buf()?;
+    if magic.starts_with(GZIP_MAGIC) {
+        return Ok(Box::new(MultiGzDecoder::new(rdr)));
+    }
+    if magic.starts_with(ZSTD_MAGIC) {
+        let mut buf = vec![];
+        rdr.read_to_end(&mut buf)?;
+        return Ok(Box::new(io::Cursor::new(decode_zstd(&buf)?)));
+    }
+    Ok(Box::new(rdr))
+}
+
+/// Decodes every zstd frame in `input`, which a decoder only does one at a
+/// time, skipping skippable frames.
+fn decode_zstd(mut input: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = vec![];
+    while !input.is_empty() {
+        match StreamingDecoder::new(&mut input) {
+            Ok(mut decoder) => {
+                decoder.read_to_end(&mut out)?;
+            }
+            Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame { length, .. })) => {
+                input = input.get(length as usize..).unwrap_or(&[]);
+            }
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err.to_string())),
+        }
+    }
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -475,94 +875,9 @@ mod tests {
                 let shortest_difference = zipped.next().map(|(o, n)| o != n).unwrap_or(false);
                 let shortest_difference = shortest_difference && zipped.all(|(o, n)| o == n);
 
-                old == new || path_0.iter().count() <= 1 || path_1.iter().count() <= 1 ||
+                old == new || path_0.len() <= 1 || path_1.len() <= 1 ||
                 shortest_difference
             }
         }
     }
-
-    mod split_benchmarks {
-        use super::super::Args;
-        use super::AlphaString;
-        use benchmark::Benchmark;
-
-        quickcheck! {
-            fn from_original(benches: Vec<Benchmark>, old: AlphaString, new: AlphaString) -> bool {
-                let AlphaString(old) = old;
-                let AlphaString(new) = new;
-                let result = Args::split_benchmarks(benches.clone(), &old, &new);
-
-                result.0.into_iter().all(|mut b| {
-                    b.name = old.clone() + &b.name;
-                    benches.contains(&b)
-                }) &&
-                result.1.into_iter().all(|mut b| {
-                    b.name = new.clone() + &b.name;
-                    benches.contains(&b)
-                })
-            }
-
-            fn non_overlapping(benches: Vec<Benchmark>,
-                               old: AlphaString,
-                               new: AlphaString)
-                               -> bool {
-                let AlphaString(old) = old;
-                let AlphaString(new) = new;
-                let result = Args::split_benchmarks(benches.clone(), &old, &new);
-                let mut benches = benches;
-
-                let results: Vec<Benchmark> = result.0
-                    .into_iter()
-                    .map(|mut b| {
-                        b.name = old.clone() + &b.name;
-                        b
-                    })
-                    .chain(result.1.into_iter().map(|mut b| {
-                        b.name = new.clone() + &b.name;
-                        b
-                    }))
-                    .collect();
-
-                for result in results {
-                    if let Some(index) = benches.iter().position(|b| b == &result) {
-                        benches.swap_remove(index);
-                    } else {
-                        return false;
-                    }
-                }
-
-                true
-            }
-
-            fn dropped_non_prefix(benches: Vec<Benchmark>,
-                                  old: AlphaString,
-                                  new: AlphaString)
-                                  -> bool {
-                let AlphaString(old) = old;
-                let AlphaString(new) = new;
-                let result = Args::split_benchmarks(benches.clone(), &old, &new);
-                let mut benches = benches;
-
-                let results: Vec<Benchmark> = result.0
-                    .into_iter()
-                    .map(|mut b| {
-                        b.name = old.clone() + &b.name;
-                        b
-                    })
-                    .chain(result.1.into_iter().map(|mut b| {
-                        b.name = new.clone() + &b.name;
-                        b
-                    }))
-                    .collect();
-
-                for result in results {
-                    if let Some(index) = benches.iter().position(|b| b == &result) {
-                        benches.swap_remove(index);
-                    }
-                }
-
-                benches.into_iter().all(|b| !(b.name.starts_with(&old) || b.name.starts_with(&new)))
-            }
-        }
-    }
 }
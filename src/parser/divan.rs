@@ -0,0 +1,250 @@
+use std::io::BufRead;
+
+use benchmark::{Benchmark, Metric};
+use error::Result;
+use super::{Origin, Parser};
+
+/// The characters that draw the tree in divan's first column.
+const TREE_CHARS: &[char] = &['│', '├', '╰', '─', ' '];
+
+/// Parses the table printed by [divan](https://github.com/nvzqz/divan).
+///
+/// Divan prints the benchmarks of each target as a tree, nested by module
+/// and by arguments. The tree is flattened into names like
+/// `target::module::bench`. A benchmark's time is its median, and its
+/// variance is the difference between the slowest and fastest sample, like
+/// libtest reports it. The mean time is available as the metric `mean`.
+/// Throughputs in bytes are read from the row below a benchmark and
+/// converted to MB/s.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Divan;
+
+/// The layout of a divan table, from its header.
+///
+/// The tree in the first column is drawn with the same character that
+/// separates the columns, so rows are split at the positions of the
+/// separators in the header rather than at every separator character.
+#[derive(Clone, Debug)]
+struct Columns {
+    /// The positions of the separators, in characters.
+    separators: Vec<usize>,
+    fastest: usize,
+    slowest: usize,
+    median: usize,
+    mean: usize,
+}
+
+impl Columns {
+    /// Parses the header of a table, e.g.,
+    /// `example  fastest │ slowest │ median │ mean │ samples │ iters`.
+    fn parse(line: &str) -> Option<Columns> {
+        let cells: Vec<&str> = line.split('│').collect();
+        let find = |name: &str| cells.iter().position(|c| c.split_whitespace().last() == Some(name));
+        Some(Columns {
+            separators: line.chars().enumerate().filter(|&(_, c)| c == '│').map(|(i, _)| i).collect(),
+            fastest: find("fastest")?,
+            slowest: find("slowest")?,
+            median: find("median")?,
+            mean: find("mean")?,
+        })
+    }
+
+    /// Splits a row of the table into its cells, or returns `None` if the
+    /// line isn't a row of this table.
+    fn cells<'a>(&self, line: &'a str) -> Option<Vec<&'a str>> {
+        let mut cells = vec![];
+        let mut start = 0;
+        let mut separators = self.separators.iter().peekable();
+        for (i, (offset, c)) in line.char_indices().enumerate() {
+            if separators.peek() == Some(&&i) {
+                if c != '│' {
+                    return None;
+                }
+                cells.push(&line[start..offset]);
+                start = offset + c.len_utf8();
+                separators.next();
+            }
+        }
+        if separators.next().is_some() {
+            return None;
+        }
+        cells.push(&line[start..]);
+        Some(cells)
+    }
+}
+
+impl Parser for Divan {
+    fn name(&self) -> &str {
+        "divan"
+    }
+
+    fn detect(&self, input: &[u8]) -> bool {
+        String::from_utf8_lossy(input).lines().any(|line| Columns::parse(line).is_some())
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Vec<Benchmark>> {
+        let mut vec: Vec<Benchmark> = vec![];
+        let mut columns: Option<Columns> = None;
+        let mut path: Vec<String> = vec![];
+        // The index in `vec` of the benchmark on the previous row, which a
+        // throughput row belongs to.
+        let mut last: Option<usize> = None;
+        let mut origin = Origin::default();
+        for line in input.lines() {
+            let line = line?;
+            if origin.update(&line) {
+                continue;
+            }
+            if let Some(header) = Columns::parse(&line) {
+                columns = Some(header);
+                path = line.split_whitespace().next().map(|s| s.to_string()).into_iter().collect();
+                last = None;
+                continue;
+            }
+            let (columns, cells) = match columns.as_ref().and_then(|c| Some((c, c.cells(&line)?))) {
+                Some(found) => found,
+                None => continue,
+            };
+            let first = cells[0].trim_start_matches(TREE_CHARS);
+            let depth = (cells[0].chars().count() - first.chars().count()) / 3;
+            let (name, fastest) = split_name(first);
+            if name.is_empty() {
+                if let (Some(i), Some(throughput)) = (last, parse_mb_per_s(cells[columns.median])) {
+                    vec[i].metrics.insert("throughput".to_string(), Metric::throughput(throughput as f64));
+                }
+                last = None;
+                continue;
+            }
+            path.truncate(depth.max(1));
+            path.push(name.to_string());
+            last = None;
+            let fastest = if columns.fastest == 0 { fastest } else { parse_ns(cells[columns.fastest]) };
+            if let Some(ns) = parse_ns(cells[columns.median]) {
+                let slowest = parse_ns(cells[columns.slowest]);
+                let variance = match (fastest, slowest) {
+                    (Some(fastest), Some(slowest)) => round_ps((slowest - fastest).max(0.0)),
+                    _ => 0.0,
+                };
+                let mut bench = Benchmark::new(&path.join("::"));
+                bench.metrics.insert("time".to_string(), Metric::time(ns, variance));
+                if let Some(mean) = parse_ns(cells[columns.mean]) {
+                    bench.metrics.insert("mean".to_string(), Metric::time(mean, 0.0));
+                }
+                last = Some(vec.len());
+                origin.apply(&mut bench);
+                vec.push(bench);
+            }
+        }
+        Ok(vec)
+    }
+}
+
+/// Splits the first cell of a row, after the tree, into the name of the
+/// node and the time in the fastest column, if any.
+fn split_name(cell: &str) -> (&str, Option<f64>) {
+    let cell = cell.trim_end();
+    let mut it = cell.rsplitn(3, ' ');
+    if let (Some(unit), Some(value), Some(name)) = (it.next(), it.next(), it.next()) {
+        if let Some(ns) = parse_ns(&format!("{} {}", value, unit)) {
+            return (name.trim_end(), Some(ns));
+        }
+    }
+    // A row with only a throughput has no name, only the value.
+    if parse_mb_per_s(cell).is_some() {
+        return ("", None);
+    }
+    (cell, None)
+}
+
+/// Splits a cell like `1.5 µs` into its number and its unit.
+fn split_value(cell: &str) -> Option<(f64, &str)> {
+    let mut it = cell.split_whitespace();
+    let value = it.next()?.parse().ok()?;
+    let unit = it.next()?;
+    if it.next().is_some() {
+        return None;
+    }
+    Some((value, unit))
+}
+
+/// Parses a time like `197.5 ns` or `1.2 µs` into nanoseconds.
+///
+/// Divan measures times below a nanosecond, so they are kept to the
+/// picosecond rather than rounded to whole nanoseconds.
+fn parse_ns(cell: &str) -> Option<f64> {
+    let (value, unit) = split_value(cell)?;
+    let scale = match unit {
+        "ps" => 1e-3,
+        "ns" => 1.0,
+        "µs" | "μs" | "us" => 1e3,
+        "ms" => 1e6,
+        "s" => 1e9,
+        _ => return None,
+    };
+    Some(round_ps(value * scale))
+}
+
+/// Rounds a time in nanoseconds to the picosecond, which also drops the
+/// error of converting it from another unit.
+fn round_ps(ns: f64) -> f64 {
+    (ns * 1e3).round() / 1e3
+}
+
+/// Parses a throughput in bytes like `5.1 GB/s` into MB/s.
+fn parse_mb_per_s(cell: &str) -> Option<u64> {
+    let (value, unit) = split_value(cell)?;
+    let scale = match unit {
+        "B/s" => 1e-6,
+        "KB/s" => 1e-3,
+        "MB/s" => 1.0,
+        "GB/s" => 1e3,
+        "TB/s" => 1e6,
+        "KiB/s" => 1024.0 / 1e6,
+        "MiB/s" => 1024.0 * 1024.0 / 1e6,
+        "GiB/s" => 1024.0 * 1024.0 * 1024.0 / 1e6,
+        _ => return None,
+    };
+    Some((value * scale).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Divan;
+    use parser::Parser;
+
+    const OUTPUT: &str = "\
+     Running benches/example.rs (target/release/deps/example-0b3ab7ed4bb2fd8a)
+Timer precision: 41 ns
+example          fastest       │ slowest       │ median        │ mean          │ samples │ iters
+├─ fibonacci     196.1 ns      │ 217 ns        │ 197.5 ns      │ 198.1 ns      │ 100     │ 3200
+├─ math                        │               │               │               │         │
+│  ├─ add        0.25 ns       │ 0.29 ns       │ 0.26 ns       │ 0.26 ns       │ 100     │ 409600
+│  ╰─ sub        0.251 ns      │ 3.1 ns        │ 2.6 ns        │ 2.6 ns        │ 100     │ 409600
+├─ copy          1.2 µs        │ 3.4 µs        │ 1.3 µs        │ 1.5 µs        │ 100     │ 100
+│                3.413 GB/s    │ 1.204 GB/s    │ 3.151 GB/s    │ 2.731 GB/s    │         │
+╰─ sort                        │               │               │               │         │
+   ├─ 10         1.2 ms        │ 1.4 ms        │ 1.25 ms       │ 1.3 ms        │ 100     │ 100
+   ╰─ 100        1.1 s         │ 1.2 s         │ 1.15 s        │ 1.16 s        │ 10      │ 10
+";
+
+    #[test]
+    fn flattens_tree() {
+        assert!(Divan.detect(OUTPUT.as_bytes()));
+        let benches = Divan.parse(&mut OUTPUT.as_bytes()).unwrap();
+        let summary: Vec<(&str, f64, f64, Option<f64>)> = benches.iter()
+            .map(|b| {
+                let time = &b.metrics["time"];
+                (&*b.name, time.value, time.variance, b.metric("throughput").map(|t| t.value))
+            })
+            .collect();
+        assert_eq!(summary,
+                   vec![("example::fibonacci", 197.5, 20.9, None),
+                        ("example::math::add", 0.26, 0.04, None),
+                        ("example::math::sub", 2.6, 2.849, None),
+                        ("example::copy", 1300.0, 2200.0, Some(3151.0)),
+                        ("example::sort::10", 1250000.0, 200000.0, None),
+                        ("example::sort::100", 1150000000.0, 100000000.0, None)]);
+        assert_eq!(benches[0].metrics["mean"].value, 198.1);
+        assert_eq!(benches[1].fmt_metric("time", true), "0.26 (+/- 0.04)");
+    }
+}
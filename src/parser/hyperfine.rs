@@ -0,0 +1,123 @@
+use std::io::{self, BufRead};
+
+use serde_json;
+
+use benchmark::{Benchmark, Direction, Metric};
+use error::Result;
+use super::Parser;
+
+/// Parses the JSON exported by
+/// [hyperfine](https://github.com/sharkdp/hyperfine) with `--export-json`.
+///
+/// Every result becomes a benchmark named by its command (or by the name
+/// given with `--command-name`). Its time is the median run time (or the
+/// mean, for old versions of hyperfine that didn't export one), and its
+/// variance is the standard deviation, as hyperfine reports them. The mean,
+/// user, system, shortest and longest times are available as the metrics
+/// `mean`, `user`, `system`, `min` and `max`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Hyperfine;
+
+#[derive(Debug, Deserialize)]
+struct Export {
+    results: Vec<Run>,
+}
+
+/// The statistics of one command, in seconds.
+///
+/// The time of every single run, exported as `times`, is deliberately left
+/// out, since these statistics already summarize it.
+#[derive(Debug, Deserialize)]
+struct Run {
+    command: String,
+    mean: f64,
+    stddev: Option<f64>,
+    median: Option<f64>,
+    user: Option<f64>,
+    system: Option<f64>,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Run {
+    fn into_benchmark(self) -> Benchmark {
+        let ns = |secs: f64| (secs * 1e9).round();
+        let mut bench = Benchmark::new(&self.command);
+        let time = Metric {
+            variance: self.stddev.map_or(0.0, ns),
+            ..Metric::new(ns(self.median.unwrap_or(self.mean)), "ns", Direction::LowerIsBetter)
+        };
+        bench.metrics.insert("time".to_string(), time);
+        let times = [("mean", Some(self.mean)), ("user", self.user), ("system", self.system),
+                     ("min", self.min), ("max", self.max)];
+        for &(name, secs) in &times {
+            if let Some(secs) = secs {
+                bench.metrics.insert(name.to_string(), Metric::new(ns(secs), "ns", Direction::LowerIsBetter));
+            }
+        }
+        bench
+    }
+}
+
+impl Parser for Hyperfine {
+    fn name(&self) -> &str {
+        "hyperfine"
+    }
+
+    fn detect(&self, input: &[u8]) -> bool {
+        serde_json::from_slice::<Export>(input).is_ok()
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Vec<Benchmark>> {
+        let export: Export = serde_json::from_reader(input).map_err(io::Error::from)?;
+        Ok(export.results.into_iter().map(Run::into_benchmark).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hyperfine;
+    use parser::{Parser, Registry};
+
+    #[test]
+    fn hyperfine() {
+        let input = r#"{
+  "results": [
+    {
+      "command": "grep -r foo",
+      "mean": 0.0123,
+      "stddev": 0.0004,
+      "median": 0.0121,
+      "user": 0.008,
+      "system": 0.004,
+      "min": 0.0117,
+      "max": 0.0139,
+      "times": [0.0117, 0.0121, 0.0139],
+      "exit_codes": [0, 0, 0]
+    },
+    {
+      "command": "rg foo",
+      "mean": 0.0021,
+      "stddev": null,
+      "times": [0.0021]
+    }
+  ]
+}"#;
+        let registry = Registry::new();
+        assert_eq!(registry.detect(input.as_bytes()).map(|p| p.name()), Some("hyperfine"));
+        let benches = Hyperfine.parse(&mut input.as_bytes()).unwrap();
+        assert_eq!(benches.len(), 2);
+        let time = |i: usize| (&*benches[i].name, benches[i].metrics["time"].value, benches[i].metrics["time"].variance);
+        assert_eq!(time(0), ("grep -r foo", 12100000.0, 400000.0));
+        assert_eq!(time(1), ("rg foo", 2100000.0, 0.0));
+        assert_eq!(benches[0].metrics["user"].value, 8000000.0);
+        assert_eq!(benches[0].metrics["min"].value, 11700000.0);
+        assert_eq!(benches[0].metrics["max"].value, 13900000.0);
+        assert_eq!(benches[1].metric_names(), vec!["mean", "time"]);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Hyperfine.parse(&mut &b"{ \"results\": 1 }"[..]).is_err());
+    }
+}
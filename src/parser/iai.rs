@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+use benchmark::{Benchmark, Direction, Metric};
+use error::Result;
+use super::{Origin, Parser};
+
+/// Parses the instruction counts printed by
+/// [iai](https://github.com/bheisler/iai) and iai-callgrind.
+///
+/// Each benchmark is a line with its name, followed by indented lines like
+/// `Instructions: 1735`. Every such line becomes a metric of the benchmark,
+/// named in lower case with underscores, e.g., `instructions`,
+/// `l1_accesses` or `estimated_cycles`, which is also its unit. Changes
+/// relative to a previous run that follow the count are ignored.
+///
+/// Since there is no time, benchmarks are compared by their estimated
+/// cycles (or their instructions, if cycles aren't reported) by default.
+/// Use `Benchmarks::select_metric` to compare by another metric.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Iai;
+
+/// Parses a line like `  Instructions:  1735 (+0.000000%)` into the name of
+/// the counter and its value.
+///
+/// iai-callgrind prints the new and old count separated by `|`, e.g.,
+/// `1734|1734`, in which case the first (new) count is used.
+fn parse_counter(line: &str) -> Option<(String, u64)> {
+    if !line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let (label, rest) = line.trim_start().split_once(':')?;
+    let value = rest.split_whitespace().next()?.split('|').next()?.parse().ok()?;
+    let name = label.trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    Some((name, value))
+}
+
+/// Builds the benchmark named `name` from its counters, if it has any.
+fn to_benchmark(name: String, counters: BTreeMap<String, u64>, origin: &Origin) -> Option<Benchmark> {
+    if counters.is_empty() {
+        return None;
+    }
+    let mut bench = Benchmark::new(&name);
+    bench.metrics = counters.into_iter()
+        .map(|(name, count)| {
+            let metric = Metric::new(count as f64, &name, Direction::LowerIsBetter);
+            (name, metric)
+        })
+        .collect();
+    origin.apply(&mut bench);
+    Some(bench)
+}
+
+impl Parser for Iai {
+    fn name(&self) -> &str {
+        "iai"
+    }
+
+    fn detect(&self, input: &[u8]) -> bool {
+        String::from_utf8_lossy(input)
+            .lines()
+            .any(|line| parse_counter(line).is_some_and(|(name, _)| name == "instructions"))
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Vec<Benchmark>> {
+        let mut vec = vec![];
+        let mut current: Option<(String, BTreeMap<String, u64>)> = None;
+        let mut origin = Origin::default();
+        for line in input.lines() {
+            let line = line?;
+            if let Some((name, value)) = parse_counter(&line) {
+                if let Some((_, ref mut counters)) = current {
+                    counters.insert(name, value);
+                }
+                continue;
+            }
+            if let Some((name, counters)) = current.take() {
+                vec.extend(to_benchmark(name, counters, &origin));
+            }
+            if origin.update(&line) {
+                continue;
+            }
+            if !line.is_empty() && !line.starts_with(char::is_whitespace) {
+                current = Some((line.trim_end().to_string(), BTreeMap::new()));
+            }
+        }
+        if let Some((name, counters)) = current {
+            vec.extend(to_benchmark(name, counters, &origin));
+        }
+        Ok(vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Iai;
+    use parser::Parser;
+
+    #[test]
+    fn iai() {
+        let input = "\
+     Running benches/my_benchmark.rs (target/release/deps/my_benchmark-4a0b1c5c)
+bench_fibonacci_short
+  Instructions:                1735
+  L1 Accesses:                 2364
+  L2 Accesses:                    1
+  RAM Accesses:                   1
+  Estimated Cycles:            2404
+
+bench_fibonacci_long
+  Instructions:            26214735 (+0.000000%)
+  L1 Accesses:             35638623 (No change)
+  L2 Accesses:                    2
+  RAM Accesses:                   1
+  Estimated Cycles:        35638668
+";
+        assert!(Iai.detect(input.as_bytes()));
+        let benches = Iai.parse(&mut input.as_bytes()).unwrap();
+        assert_eq!(benches.len(), 2);
+        assert_eq!(benches[0].name, "bench_fibonacci_short");
+        assert_eq!(benches[0].metrics["estimated_cycles"].value, 2404.0);
+        assert_eq!(benches[0].metrics["l1_accesses"].value, 2364.0);
+        assert_eq!(benches[0].metrics["l1_accesses"].unit, "l1_accesses");
+        assert_eq!(benches[1].name, "bench_fibonacci_long");
+        assert_eq!(benches[1].metrics["instructions"].value, 26214735.0);
+        assert_eq!(benches[1].metrics.len(), 5);
+    }
+
+    #[test]
+    fn iai_callgrind() {
+        let input = "\
+my_benchmark::bench_group::bench_fibonacci short:10
+  Instructions:                1734|1730            (+0.23121%)
+  L1 Hits:                     2359|2355            (+0.16985%)
+  L2 Hits:                        0|0               (No change)
+  RAM Hits:                       3|3               (No change)
+  Total read+write:            2362|2358            (+0.16964%)
+  Estimated Cycles:            2464|2460            (+0.16260%)
+";
+        let benches = Iai.parse(&mut input.as_bytes()).unwrap();
+        assert_eq!(benches.len(), 1);
+        assert_eq!(benches[0].name, "my_benchmark::bench_group::bench_fibonacci short:10");
+        assert_eq!(benches[0].metrics["estimated_cycles"].value, 2464.0);
+        assert_eq!(benches[0].metrics["total_read_write"].value, 2362.0);
+    }
+}
@@ -0,0 +1,593 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{BufRead, Read};
+use std::path::Path;
+
+use regex::Regex;
+use regex::bytes::Regex as BytesRegex;
+use serde_json;
+
+use benchmark::{Benchmark, Failure, Metric, Status};
+use error::{Error, ParseError, Result};
+
+pub use self::divan::Divan;
+pub use self::hyperfine::Hyperfine;
+pub use self::iai::Iai;
+
+mod divan;
+mod hyperfine;
+mod iai;
+
+/// A parser for one format of benchmark output.
+///
+/// Implementations turn the output of a benchmark harness into a list of
+/// benchmarks. They are registered in a [`Registry`](struct.Registry.html)
+/// under their name, which is how they are selected by users.
+pub trait Parser {
+    /// The name of the format, e.g., `libtest`.
+    fn name(&self) -> &str;
+
+    /// Returns true if `input` looks like it is in this parser's format.
+    ///
+    /// This is used to pick a parser automatically. `input` is the complete
+    /// input, which need not be valid UTF-8.
+    fn detect(&self, input: &[u8]) -> bool;
+
+    /// Parses all benchmarks from `input`.
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Vec<Benchmark>>;
+
+    /// Parses all benchmarks from `input`, along with an error for every
+    /// line that looks like the result of a benchmark but couldn't be
+    /// parsed, and the benchmarks that failed or were ignored.
+    ///
+    /// The default implementation reports neither.
+    fn parse_with_diagnostics(&self, input: &mut dyn BufRead) -> Result<Parsed> {
+        Ok(Parsed {
+            benchmarks: self.parse(input)?,
+            ..Parsed::default()
+        })
+    }
+}
+
+/// Everything a parser found in its input.
+#[derive(Clone, Debug, Default)]
+pub struct Parsed {
+    /// The benchmarks with results.
+    pub benchmarks: Vec<Benchmark>,
+    /// An error for every line that looks like the result of a benchmark,
+    /// but couldn't be parsed.
+    pub errors: Vec<ParseError>,
+    /// The benchmarks that failed or were ignored.
+    pub failures: Vec<Failure>,
+}
+
+/// A set of parsers, keyed by their name.
+pub struct Registry {
+    parsers: Vec<Box<dyn Parser>>,
+}
+
+impl Registry {
+    /// Creates a registry containing all parsers provided by this crate.
+    pub fn new() -> Registry {
+        let mut registry = Registry::empty();
+        registry.register(Box::new(Hyperfine));
+        registry.register(Box::new(LibtestJson));
+        registry.register(Box::new(Divan));
+        registry.register(Box::new(Iai));
+        registry.register(Box::new(Libtest));
+        registry
+    }
+
+    /// Creates a registry without any parsers.
+    pub fn empty() -> Registry {
+        Registry { parsers: vec![] }
+    }
+
+    /// Adds a parser to this registry.
+    ///
+    /// If a parser with the same name is already registered, it is replaced.
+    /// Otherwise, the parser is tried after all existing ones when detecting
+    /// the format of an input.
+    pub fn register(&mut self, parser: Box<dyn Parser>) {
+        match self.parsers.iter().position(|p| p.name() == parser.name()) {
+            Some(i) => self.parsers[i] = parser,
+            None => self.parsers.push(parser),
+        }
+    }
+
+    /// Returns the parser registered under `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&dyn Parser> {
+        self.parsers.iter().find(|p| p.name() == name).map(|p| &**p)
+    }
+
+    /// Returns the names of all registered parsers, in detection order.
+    pub fn names(&self) -> Vec<&str> {
+        self.parsers.iter().map(|p| p.name()).collect()
+    }
+
+    /// Returns the first parser that recognizes `input`, if any.
+    pub fn detect(&self, input: &[u8]) -> Option<&dyn Parser> {
+        self.parsers.iter().find(|p| p.detect(input)).map(|p| &**p)
+    }
+
+    /// Parses benchmarks from `input` with the parser named `format`.
+    ///
+    /// If `format` is `auto`, the parser is chosen with `detect`. If no
+    /// parser recognizes the input, it is parsed as `libtest` output (which
+    /// yields no benchmarks, but isn't an error).
+    pub fn parse<R: Read>(&self, format: &str, input: R) -> Result<Vec<Benchmark>> {
+        self.parse_with_diagnostics(format, input).map(|parsed| parsed.benchmarks)
+    }
+
+    /// Like `parse`, but also returns the lines that couldn't be parsed and
+    /// the benchmarks without results (see `Parser::parse_with_diagnostics`).
+    ///
+    /// Before parsing, the input is cleaned up like a terminal would show
+    /// it: ANSI escape codes are removed, and so is everything up to the
+    /// last carriage return on each line. Timestamps that CI systems prefix
+    /// lines with, like GitHub Actions' `2024-01-01T00:00:00.0000000Z `, are
+    /// removed as well.
+    pub fn parse_with_diagnostics<R: Read>(&self, format: &str, mut input: R) -> Result<Parsed> {
+        let mut buf = vec![];
+        input.read_to_end(&mut buf)?;
+        let buf = normalize(&buf);
+        let parser = if format == "auto" {
+            self.detect(&buf).or_else(|| self.get("libtest"))
+        } else {
+            self.get(format)
+        };
+        match parser {
+            Some(parser) => parser.parse_with_diagnostics(&mut &buf[..]),
+            None => {
+                Err(Error::UnknownFormat {
+                    name: format.to_string(),
+                    known: self.names().into_iter().map(|n| n.to_string()).collect(),
+                })
+            }
+        }
+    }
+}
+
+lazy_static! {
+    static ref ANSI_REGEX: BytesRegex = BytesRegex::new(r"(?x)
+        \x1b\[[0-?]*[\x20-/]*[@-~]                # CSI, e.g., colors
+        |\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)       # OSC, e.g., hyperlinks
+        |\x1b[@-Z\\-_]                            # other two-byte escapes
+    ").unwrap();
+    static ref TIMESTAMP_REGEX: BytesRegex =
+        BytesRegex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?Z ").unwrap();
+}
+
+/// Removes ANSI escape codes, carriage returns and CI timestamps from a
+/// log, without changing its number of lines.
+fn normalize(input: &[u8]) -> Vec<u8> {
+    let input = ANSI_REGEX.replace_all(input, &b""[..]);
+    let mut out = Vec::with_capacity(input.len());
+    for (i, line) in input.split(|&b| b == b'\n').enumerate() {
+        if i > 0 {
+            out.push(b'\n');
+        }
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line = match line.iter().rposition(|&b| b == b'\r') {
+            Some(cr) => &line[cr + 1..],
+            None => line,
+        };
+        let start = TIMESTAMP_REGEX.find(line).map_or(0, |m| m.end());
+        out.extend_from_slice(&line[start..]);
+    }
+    out
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::new()
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Registry").field("parsers", &self.names()).finish()
+    }
+}
+
+lazy_static! {
+    static ref STATUS_REGEX: Regex = Regex::new(
+        r"^test\s+(?P<name>\S+)\s+\.\.\.\s+(?P<status>FAILED|ignored)(,.*)?\s*$"
+    ).unwrap();
+    static ref SECTION_REGEX: Regex = Regex::new(r"^---- (?P<name>\S+) stdout ----\s*$").unwrap();
+    static ref OLD_PANIC_REGEX: Regex = Regex::new(r"panicked at '(?P<message>.*)', \S+$").unwrap();
+    static ref RUNNING_REGEX: Regex = Regex::new(r"(?x)
+        ^\s*Running\s+
+        (?:(?P<unittests>unittests\s+)?\S+\s+\((?P<binary>[^)]+)\)  # Running benches/foo.rs (target/.../foo-hash)
+          |(?P<bare>\S*[/\\]\S+))                      # Running target/.../foo-hash
+        \s*$
+    ").unwrap();
+}
+
+/// The bench target and crate that lines of output belong to.
+///
+/// Before running each binary, `cargo bench` prints a line like
+/// `Running benches/foo.rs (target/release/deps/foo-0b3ab7ed4bb2fd8a)`, on
+/// stderr. The target is named after the binary, without the hash that
+/// cargo appends. That line doesn't name the crate, but cargo runs the
+/// targets of each crate together, starting with its library (or binary),
+/// whose line reads `Running unittests src/lib.rs (...)` and whose binary is
+/// named after the crate. So the crate of a target is taken from the last
+/// such line before it.
+///
+/// A crate without a library or binary has no such line, so its targets
+/// would be attributed to the crate before it. This can only be detected
+/// when a target runs twice after the same `unittests` line, in which case
+/// the crate is unknown until the next one.
+#[derive(Clone, Debug, Default)]
+struct Origin {
+    target: Option<String>,
+    krate: Option<String>,
+    /// The targets run since the last `unittests` line.
+    seen: HashSet<String>,
+}
+
+impl Origin {
+    /// Updates the origin if `line` is a `Running` line, and returns whether
+    /// it was one.
+    fn update(&mut self, line: &str) -> bool {
+        let caps = match RUNNING_REGEX.captures(line) {
+            Some(caps) => caps,
+            None => return false,
+        };
+        let binary = caps.name("binary").or_else(|| caps.name("bare")).map_or("", |m| m.as_str());
+        let stem = Path::new(binary).file_stem().map_or(binary.into(), |s| s.to_string_lossy());
+        let target = match stem.rsplit_once('-') {
+            Some((name, hash)) if !hash.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit()) => name,
+            _ => &stem,
+        };
+        if caps.name("unittests").is_some() {
+            self.krate = Some(target.to_string());
+            self.seen.clear();
+        }
+        if !self.seen.insert(target.to_string()) {
+            self.krate = None;
+        }
+        self.target = Some(target.to_string());
+        true
+    }
+
+    /// Records this origin on `bench`.
+    fn apply(&self, bench: &mut Benchmark) {
+        bench.target = self.target.clone();
+        bench.krate = self.krate.clone();
+    }
+
+    /// Records this origin on `failure`.
+    fn apply_failure(&self, failure: &mut Failure) {
+        failure.target = self.target.clone();
+        failure.krate = self.krate.clone();
+    }
+}
+
+/// Parses the plain text output of `cargo bench`, as produced by libtest.
+///
+/// Besides the results, the lines `test foo ... FAILED` and
+/// `test foo ... ignored` are reported as failures. The message of a panic
+/// is read from the failures section that libtest prints after all results.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Libtest;
+
+impl Parser for Libtest {
+    fn name(&self) -> &str {
+        "libtest"
+    }
+
+    fn detect(&self, input: &[u8]) -> bool {
+        String::from_utf8_lossy(input).lines().any(|line| line.parse::<Benchmark>().is_ok())
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Vec<Benchmark>> {
+        self.parse_with_diagnostics(input).map(|parsed| parsed.benchmarks)
+    }
+
+    fn parse_with_diagnostics(&self, input: &mut dyn BufRead) -> Result<Parsed> {
+        let mut parsed = Parsed::default();
+        // The name of the benchmark whose failure output is being read, and
+        // the lines read so far.
+        let mut section: Option<(String, Vec<String>)> = None;
+        let mut origin = Origin::default();
+        for (i, result) in input.lines().enumerate() {
+            let line = result?;
+            if origin.update(&line) {
+                continue;
+            }
+            if let Some(caps) = SECTION_REGEX.captures(&line) {
+                finish_section(&mut parsed.failures, section.take());
+                section = Some((caps["name"].to_string(), vec![]));
+                continue;
+            }
+            if line == "failures:" || line.starts_with("test result:") {
+                finish_section(&mut parsed.failures, section.take());
+            }
+            if let Some((_, ref mut lines)) = section {
+                lines.push(line);
+                continue;
+            }
+            if let Some(caps) = STATUS_REGEX.captures(&line) {
+                let status = if &caps["status"] == "FAILED" { Status::Failed } else { Status::Ignored };
+                let mut failure = Failure::new(&caps["name"], status);
+                origin.apply_failure(&mut failure);
+                parsed.failures.push(failure);
+                continue;
+            }
+            match line.parse::<Benchmark>() {
+                Ok(mut bench) => {
+                    origin.apply(&mut bench);
+                    parsed.benchmarks.push(bench);
+                }
+                Err(mut err) => {
+                    if err.is_malformed() {
+                        err.line = i + 1;
+                        parsed.errors.push(err);
+                    }
+                }
+            }
+        }
+        finish_section(&mut parsed.failures, section);
+        Ok(parsed)
+    }
+}
+
+/// Attaches the panic message in the output of a failed benchmark to its
+/// failure.
+fn finish_section(failures: &mut [Failure], section: Option<(String, Vec<String>)>) {
+    let (name, lines) = match section {
+        Some(section) => section,
+        None => return,
+    };
+    if let Some(failure) = failures.iter_mut().find(|f| f.name == name) {
+        failure.message = panic_message(&lines);
+    }
+}
+
+/// Finds the message of a panic in the output of a benchmark.
+///
+/// Older versions of Rust print `panicked at 'message', src/lib.rs:1:2`,
+/// newer ones `panicked at src/lib.rs:1:2:` followed by the message on the
+/// next lines.
+fn panic_message(lines: &[String]) -> Option<String> {
+    let start = lines.iter().position(|l| l.contains("panicked at"))?;
+    if let Some(caps) = OLD_PANIC_REGEX.captures(&lines[start]) {
+        return Some(caps["message"].to_string());
+    }
+    let message: Vec<&str> = lines[start + 1..]
+        .iter()
+        .map(|l| l.trim_end())
+        .take_while(|l| !l.is_empty() && !l.starts_with("note: "))
+        .collect();
+    if message.is_empty() {
+        None
+    } else {
+        Some(message.join("\n"))
+    }
+}
+
+/// Parses the JSON output of libtest, as produced by
+/// `cargo bench -- -Z unstable-options --format json`.
+///
+/// Every line is a JSON object describing one event. Only `bench` events
+/// produce benchmarks, and lines that aren't JSON are ignored.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LibtestJson;
+
+#[derive(Debug, Deserialize)]
+struct JsonEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    name: Option<String>,
+    median: Option<f64>,
+    deviation: Option<f64>,
+    mib_per_second: Option<f64>,
+}
+
+impl JsonEvent {
+    fn parse(line: &str) -> Option<JsonEvent> {
+        if !line.trim_start().starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(line).ok()
+    }
+
+    fn into_benchmark(self) -> Option<Benchmark> {
+        if self.kind != "bench" {
+            return None;
+        }
+        let mut bench = Benchmark::new(&self.name?);
+        let time = Metric::time(self.median?, self.deviation.unwrap_or(0.0));
+        bench.metrics.insert("time".to_string(), time);
+        if let Some(throughput) = self.mib_per_second {
+            bench.metrics.insert("throughput".to_string(), Metric::throughput(throughput));
+        }
+        Some(bench)
+    }
+}
+
+impl Parser for LibtestJson {
+    fn name(&self) -> &str {
+        "libtest-json"
+    }
+
+    fn detect(&self, input: &[u8]) -> bool {
+        String::from_utf8_lossy(input).lines().any(|line| JsonEvent::parse(line).is_some())
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Vec<Benchmark>> {
+        let mut vec = vec![];
+        let mut origin = Origin::default();
+        for result in input.lines() {
+            let line = result?;
+            if origin.update(&line) {
+                continue;
+            }
+            if let Some(mut bench) = JsonEvent::parse(&line).and_then(JsonEvent::into_benchmark) {
+                origin.apply(&mut bench);
+                vec.push(bench);
+            }
+        }
+        Ok(vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Libtest, LibtestJson, Origin, Parser, Registry};
+    use benchmark::{Benchmark, Status};
+
+    fn as_json(b: &Benchmark) -> String {
+        let mut res = format!(r#"{{ "type": "bench", "name": "{}", "median": {}, "deviation": {}"#,
+                              b.name,
+                              b.metrics["time"].value,
+                              b.metrics["time"].variance);
+        if let Some(throughput) = b.metric("throughput") {
+            res = format!(r#"{}, "mib_per_second": {}"#, res, throughput.value);
+        }
+        res + " }"
+    }
+
+    fn deep_eq(b1: &Benchmark, b2: &Benchmark) -> bool {
+        b1.name == b2.name && b1.metrics == b2.metrics
+    }
+
+    quickcheck! {
+        fn json_reparse(benches: Vec<Benchmark>) -> bool {
+            let mut input = String::from(r#"{ "type": "suite", "event": "started", "test_count": 0 }"#);
+            for b in &benches {
+                input.push('\n');
+                input.push_str(&as_json(b));
+            }
+            let parsed = LibtestJson.parse(&mut input.as_bytes()).unwrap();
+            parsed.len() == benches.len() &&
+            parsed.iter().zip(&benches).all(|(b1, b2)| deep_eq(b1, b2))
+        }
+
+        fn detects_json(benches: Vec<Benchmark>) -> bool {
+            let input: Vec<String> = benches.iter().map(as_json).collect();
+            let input = input.join("\n");
+            let registry = Registry::new();
+            benches.is_empty() ||
+            registry.detect(input.as_bytes()).map(|p| p.name()) == Some("libtest-json")
+        }
+    }
+
+    #[test]
+    fn detects_libtest() {
+        let input = "running 1 test\n\
+                     test fib ... bench:       1,200 ns/iter (+/- 40)\n";
+        let registry = Registry::new();
+        assert_eq!(registry.detect(input.as_bytes()).map(|p| p.name()), Some("libtest"));
+        assert!(!LibtestJson.detect(input.as_bytes()));
+        assert_eq!(Libtest.parse(&mut input.as_bytes()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn json_fractions() {
+        let input = r#"{ "type": "bench", "name": "add", "median": 0.37, "deviation": 0.02, "mib_per_second": 2577.4 }"#;
+        let parsed = LibtestJson.parse(&mut input.as_bytes()).unwrap();
+        let time = &parsed[0].metrics["time"];
+        assert_eq!((time.value, time.variance), (0.37, 0.02));
+        assert_eq!(parsed[0].metrics["throughput"].value, 2577.4);
+    }
+
+    #[test]
+    fn malformed_lines() {
+        let input = "test ok ... bench:       1,200 ns/iter (+/- 40)\n\
+                     test no_unit ... bench:       1,200 (+/- 40)\n\
+                     unrelated line\n\
+                     test no_variance ... bench:       1,200 ns/iter\n";
+        let parsed = Libtest.parse_with_diagnostics(&mut input.as_bytes()).unwrap();
+        assert_eq!(parsed.benchmarks.len(), 1);
+        let errs = parsed.errors;
+        let lines: Vec<usize> = errs.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 4]);
+        assert_eq!(errs[0].to_string(),
+                   "line 2: expected a time like `1,234 ns/iter` after `bench:`");
+    }
+
+    #[test]
+    fn failures() {
+        let input = "\
+running 4 tests
+test tests::unit ... ignored
+test bench_new_panic ... FAILED
+test bench_ok ... bench:       1,200 ns/iter (+/- 40)
+test bench_old_panic ... FAILED
+
+failures:
+
+---- bench_new_panic stdout ----
+
+thread 'main' panicked at src/lib.rs:10:5:
+index out of bounds
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+---- bench_old_panic stdout ----
+thread 'main' panicked at 'boom', src/lib.rs:20:5
+
+
+failures:
+    bench_new_panic
+    bench_old_panic
+
+test result: FAILED. 0 passed; 2 failed; 1 ignored; 1 measured; 0 filtered out
+";
+        let parsed = Libtest.parse_with_diagnostics(&mut input.as_bytes()).unwrap();
+        assert_eq!(parsed.benchmarks.len(), 1);
+        assert!(parsed.errors.is_empty());
+        let failures: Vec<(&str, Status, Option<&str>)> = parsed.failures
+            .iter()
+            .map(|f| (&*f.name, f.status, f.message.as_deref()))
+            .collect();
+        assert_eq!(failures,
+                   vec![("tests::unit", Status::Ignored, None),
+                        ("bench_new_panic", Status::Failed, Some("index out of bounds")),
+                        ("bench_old_panic", Status::Failed, Some("boom"))]);
+    }
+
+    #[test]
+    fn origin() {
+        let mut origin = Origin::default();
+        let mut update = |line| {
+            let running = origin.update(line);
+            (running, origin.target.clone(), origin.krate.clone())
+        };
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(update("     Running unittests src/lib.rs (target/release/deps/my_crate-4a0b1c5c)"),
+                   (true, some("my_crate"), some("my_crate")));
+        assert_eq!(update("     Running benches/foo.rs (target/release/deps/foo-0b3ab7ed4bb2fd8a)"),
+                   (true, some("foo"), some("my_crate")));
+        assert_eq!(update("     Running target/release/deps/bench_parse-4a0b1c5c.exe"),
+                   (true, some("bench_parse"), some("my_crate")));
+        assert_eq!(update("running 3 tests"), (false, some("bench_parse"), some("my_crate")));
+        assert_eq!(update("     Running benches/foo.rs (target/release/deps/foo-7f30b9e6c2d4a185)"),
+                   (true, some("foo"), None));
+        assert_eq!(update("     Running unittests src/lib.rs (target/release/deps/other-0e93c4d7)"),
+                   (true, some("other"), some("other")));
+        assert_eq!(update("     Running benches/foo.rs (target/release/deps/foo-b1e7a03c95d2f864)"),
+                   (true, some("foo"), some("other")));
+    }
+
+    #[test]
+    fn normalize() {
+        let input = "2024-01-01T00:00:00.0000000Z running 1 test\r\n\
+                     2024-01-01T00:00:01.1234567Z test \x1b[1mfib\x1b[0m ... \
+                     bench: \x1b[32m1,200\x1b[0m ns/iter (+/- 40)\r\n\
+                     Benchmarking sort: Warming up\rsort  time: [1.2 us]\n";
+        assert_eq!(String::from_utf8(super::normalize(input.as_bytes())).unwrap(),
+                   "running 1 test\n\
+                    test fib ... bench: 1,200 ns/iter (+/- 40)\n\
+                    sort  time: [1.2 us]\n");
+        let benches = Registry::new().parse("auto", input.as_bytes()).unwrap();
+        assert_eq!(benches.len(), 1);
+    }
+
+    #[test]
+    fn unknown_format() {
+        assert!(Registry::new().parse("nope", &b""[..]).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::env;
+
+/// The number of character cells on each side of the axis.
+const HALF_WIDTH: usize = 10;
+
+/// Returns true if the environment's locale says the terminal can display
+/// UTF-8.
+///
+/// As with C's `setlocale`, the first of `LC_ALL`, `LC_CTYPE` and `LANG`
+/// that is set and non-empty decides.
+pub fn unicode_supported() -> bool {
+    for var in &["LC_ALL", "LC_CTYPE", "LANG"] {
+        if let Some(value) = env::var_os(var) {
+            if value.is_empty() {
+                continue;
+            }
+            let value = value.to_string_lossy().to_lowercase();
+            return value.contains("utf-8") || value.contains("utf8");
+        }
+    }
+    false
+}
+
+/// Draws a bar for `ratio`, centred on an axis that represents no change.
+///
+/// Negative ratios (improvements) extend to the left of the axis and
+/// positive ratios (regressions) to the right. `max` is the largest
+/// absolute ratio that will be drawn, and fills one side completely. Larger
+/// ratios, including infinite ones, are clipped to it.
+///
+/// With `unicode`, bars are drawn with block characters at a resolution of
+/// half a cell. Otherwise, they are drawn in ASCII with whole cells only.
+pub fn bar(ratio: f64, max: f64, unicode: bool) -> String {
+    let (axis, full, half_left, half_right) = if unicode {
+        ('│', '█', '▐', '▌')
+    } else {
+        ('|', '#', ' ', ' ')
+    };
+    let halves = if max > 0f64 {
+        ((ratio.abs() / max).min(1f64) * (HALF_WIDTH * 2) as f64).round() as usize
+    } else {
+        0
+    };
+    // ASCII has no half cells, so round them up to whole ones.
+    let halves = if unicode { halves } else { halves.div_ceil(2) * 2 };
+    let halves = halves.min(HALF_WIDTH * 2);
+    let (fulls, partial) = (halves / 2, halves % 2 == 1);
+
+    let mut side = String::with_capacity(HALF_WIDTH * 3);
+    let mut bar = String::with_capacity(HALF_WIDTH * 6 + 3);
+    if ratio < 0f64 {
+        // The bar grows to the left, so it is right-aligned against the axis.
+        let used = fulls + partial as usize;
+        side.extend((0..HALF_WIDTH - used).map(|_| ' '));
+        if partial {
+            side.push(half_left);
+        }
+        side.extend((0..fulls).map(|_| full));
+        bar.push_str(&side);
+        bar.push(axis);
+        bar.extend((0..HALF_WIDTH).map(|_| ' '));
+    } else {
+        let used = fulls + partial as usize;
+        side.extend((0..fulls).map(|_| full));
+        if partial {
+            side.push(half_right);
+        }
+        side.extend((0..HALF_WIDTH - used).map(|_| ' '));
+        bar.extend((0..HALF_WIDTH).map(|_| ' '));
+        bar.push(axis);
+        bar.push_str(&side);
+    }
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bar, HALF_WIDTH};
+
+    quickcheck! {
+        fn constant_width(ratio: f64, max: f64, unicode: bool) -> bool {
+            let (ratio, max) = (ratio.abs() % 10f64 - 5f64, max.abs() % 5f64);
+            bar(ratio, max, unicode).chars().count() == HALF_WIDTH * 2 + 1
+        }
+
+        fn axis_in_middle(ratio: f64, max: f64, unicode: bool) -> bool {
+            let (ratio, max) = (ratio.abs() % 10f64 - 5f64, max.abs() % 5f64);
+            let axis = if unicode { '│' } else { '|' };
+            bar(ratio, max, unicode).chars().nth(HALF_WIDTH) == Some(axis)
+        }
+
+        fn bar_on_correct_side(ratio: f64, unicode: bool) -> bool {
+            let ratio = ratio.abs() % 10f64 - 5f64;
+            let drawn = bar(ratio, 5f64, unicode);
+            let (left, right) = drawn.split_at(drawn.char_indices().nth(HALF_WIDTH).unwrap().0);
+            if ratio < 0f64 {
+                right.chars().skip(1).all(|c| c == ' ')
+            } else {
+                left.chars().all(|c| c == ' ')
+            }
+        }
+    }
+
+    #[test]
+    fn largest_fills_side() {
+        assert_eq!(bar(-2f64, 2f64, true), "██████████│          ");
+        assert_eq!(bar(2f64, 2f64, false), "          |##########");
+    }
+
+    #[test]
+    fn infinite_is_clipped() {
+        assert_eq!(bar(f64::INFINITY, 2f64, false), "          |##########");
+        assert_eq!(bar(f64::INFINITY, 0f64, true), "          │          ");
+    }
+}
@@ -0,0 +1,140 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use benchmark::{Comparison, PairedBenchmarks, Status};
+use error::Result;
+use super::{Context, Reporter};
+
+/// Reports regressions as GitHub Actions workflow commands.
+///
+/// Regressions of at least `Context::threshold` percent (or every
+/// regression, without a threshold) are reported with `::error`, smaller
+/// ones with `::warning`. Benchmarks that failed in the new set are
+/// reported with `::error` as well.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Github {
+    /// A file to append a Markdown summary of all comparisons to.
+    ///
+    /// In a workflow, this is the file named by `GITHUB_STEP_SUMMARY`.
+    pub summary: Option<PathBuf>,
+}
+
+impl Github {
+    /// Creates a reporter that doesn't write a summary.
+    pub fn new() -> Github {
+        Github { summary: None }
+    }
+}
+
+impl Reporter for Github {
+    fn report(&self, benches: &PairedBenchmarks, ctx: &Context, wtr: &mut dyn Write) -> Result<()> {
+        for c in benches.comparisons().iter().filter(|c| c.is_regression()) {
+            let level = if c.fails(ctx.threshold) { "error" } else { "warning" };
+            let message = format!("{} regressed by {:.2}%: {} -> {} {}",
+                                  c.old.name,
+                                  c.diff_ratio * 100f64,
+                                  c.old_value(),
+                                  c.new_value(),
+                                  ctx.unit);
+            writeln!(wtr,
+                     "::{} title={}::{}",
+                     level,
+                     escape_property(&format!("benchmark regression: {}", c.old.name)),
+                     escape_data(&message))?;
+        }
+        for f in benches.failures_new().iter().filter(|f| f.status == Status::Failed) {
+            writeln!(wtr,
+                     "::error title={}::{}",
+                     escape_property(&format!("benchmark failure: {}", f.name)),
+                     escape_data(&f.describe(&ctx.name_new)))?;
+        }
+
+        if let Some(ref path) = self.summary {
+            let mut summary = OpenOptions::new().append(true).create(true).open(path)?;
+            write_summary(&mut summary, ctx, benches)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a Markdown summary of the comparisons, suitable for a job summary.
+fn write_summary(wtr: &mut dyn Write, ctx: &Context, benches: &PairedBenchmarks) -> io::Result<()> {
+    let (name_old, name_new, threshold) = (&*ctx.name_old, &*ctx.name_new, ctx.threshold);
+    let cmps = benches.comparisons();
+    let failures = cmps.iter().filter(|c| c.fails(threshold)).count();
+    let regressions = cmps.iter().filter(|c| c.is_regression()).count();
+
+    writeln!(wtr, "## Benchmark comparison: {} vs {}", name_old, name_new)?;
+    writeln!(wtr)?;
+    writeln!(wtr,
+             "{} compared, {} regressed, {} failed",
+             cmps.len(),
+             regressions,
+             failures)?;
+    writeln!(wtr)?;
+    if !cmps.is_empty() {
+        writeln!(wtr,
+                 "| | name | {old} {unit} | {new} {unit} | diff {unit} | diff % | speedup |",
+                 old = escape_cell(name_old),
+                 new = escape_cell(name_new),
+                 unit = escape_cell(&ctx.unit))?;
+        writeln!(wtr, "|---|---|--:|--:|--:|--:|--:|")?;
+        for c in cmps {
+            writeln!(wtr,
+                     "| {} | {} | {} | {} | {} | {:.2}% | x {:.2} |",
+                     status(c, threshold),
+                     escape_cell(&c.old.name),
+                     c.old.fmt_metric(&c.metric, false),
+                     c.new.fmt_metric(&c.metric, false),
+                     c.fmt_diff(),
+                     c.diff_ratio * 100f64,
+                     c.speedup)?;
+        }
+        writeln!(wtr)?;
+    }
+    for &(missing, absent) in &[(benches.missing_old(), name_new), (benches.missing_new(), name_old)] {
+        if missing.is_empty() {
+            continue;
+        }
+        let names: Vec<String> = missing.iter().map(|b| format!("`{}`", b.name)).collect();
+        writeln!(wtr, "Not found in {}: {}", absent, names.join(", "))?;
+        writeln!(wtr)?;
+    }
+    for &(failures, name) in &[(benches.failures_old(), name_old), (benches.failures_new(), name_new)] {
+        for f in failures {
+            writeln!(wtr, "- {}", f.describe(name))?;
+        }
+        if !failures.is_empty() {
+            writeln!(wtr)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns a marker for the status of a comparison in the summary table.
+fn status(c: &Comparison, threshold: Option<u8>) -> &'static str {
+    if c.fails(threshold) {
+        ":x:"
+    } else if c.is_regression() {
+        ":warning:"
+    } else {
+        ":white_check_mark:"
+    }
+}
+
+/// Escapes the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Escapes text for use in a Markdown table cell.
+fn escape_cell(s: &str) -> String {
+    s.replace('|', "\\|")
+}
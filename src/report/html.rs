@@ -0,0 +1,293 @@
+use std::io::{self, Write};
+
+use benchmark::{Comparison, PairedBenchmarks};
+use error::Result;
+use super::{Context, Reporter};
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; }
+h2 { font-size: 1.15em; margin-top: 2em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.25em 0.75em; border-bottom: 1px solid #ddd; }
+th { cursor: pointer; text-align: left; background: #f4f4f4; user-select: none; }
+th.sorted-asc::after { content: " \25B2"; }
+th.sorted-desc::after { content: " \25BC"; }
+td.num { text-align: right; font-family: monospace; }
+tr.regression td { color: #b00; }
+tr.improvement td { color: #070; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0.25em 1em; }
+dt { font-weight: bold; }
+dd { margin: 0; font-family: monospace; }
+.chart { display: grid; grid-template-columns: max-content 40em max-content; gap: 2px 0.75em; align-items: center; }
+.chart .label { font-family: monospace; font-size: 0.9em; }
+.chart .track { position: relative; height: 1em; background: #f4f4f4; }
+.chart .axis { position: absolute; left: 50%; top: 0; bottom: 0; border-left: 1px solid #888; }
+.chart .bar { position: absolute; top: 0; bottom: 0; }
+.chart .bar.regression { background: #d55; }
+.chart .bar.improvement { background: #5a5; }
+.chart .value { font-family: monospace; font-size: 0.9em; text-align: right; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable").forEach(function(table) {
+  var headers = table.querySelectorAll("th");
+  headers.forEach(function(th, col) {
+    th.addEventListener("click", function() {
+      var asc = !th.classList.contains("sorted-asc");
+      headers.forEach(function(h) { h.classList.remove("sorted-asc", "sorted-desc"); });
+      th.classList.add(asc ? "sorted-asc" : "sorted-desc");
+      var body = table.tBodies[0];
+      var rows = Array.prototype.slice.call(body.rows);
+      rows.sort(function(a, b) {
+        var x = a.cells[col].getAttribute("data-value");
+        var y = b.cells[col].getAttribute("data-value");
+        var nx = parseFloat(x), ny = parseFloat(y);
+        var ord = (isNaN(nx) || isNaN(ny)) ? x.localeCompare(y) : nx - ny;
+        return asc ? ord : -ord;
+      });
+      rows.forEach(function(row) { body.appendChild(row); });
+    });
+  });
+});
+"#;
+
+/// Renders the comparisons as a self-contained HTML page.
+///
+/// The table and chart contain the comparisons that pass the context's
+/// filters, while the summary statistics and the missing benchmarks are
+/// always computed from all benchmarks.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Html;
+
+impl Reporter for Html {
+    fn report(&self, benches: &PairedBenchmarks, ctx: &Context, wtr: &mut dyn Write) -> Result<()> {
+        let (name_old, name_new) = (&*ctx.name_old, &*ctx.name_new);
+        let shown = ctx.shown(benches);
+        writeln!(wtr, "<!DOCTYPE html>")?;
+        writeln!(wtr, "<html>")?;
+        writeln!(wtr, "<head>")?;
+        writeln!(wtr, "<meta charset=\"utf-8\">")?;
+        writeln!(wtr,
+                 "<title>benchcmp: {} vs {}</title>",
+                 escape(name_old),
+                 escape(name_new))?;
+        writeln!(wtr, "<style>{}</style>", STYLE)?;
+        writeln!(wtr, "</head>")?;
+        writeln!(wtr, "<body>")?;
+        writeln!(wtr, "<h1>{} vs {}</h1>", escape(name_old), escape(name_new))?;
+
+        write_summary(wtr, benches, &shown)?;
+        write_table(wtr, name_old, name_new, &ctx.unit, &shown, ctx.variance)?;
+        write_chart(wtr, &shown)?;
+        write_missing(wtr, name_old, name_new, &ctx.unit, benches, ctx.variance)?;
+
+        writeln!(wtr, "<script>{}</script>", SCRIPT)?;
+        writeln!(wtr, "</body>")?;
+        writeln!(wtr, "</html>")?;
+        Ok(())
+    }
+}
+
+fn write_summary(wtr: &mut dyn Write,
+                 benches: &PairedBenchmarks,
+                 shown: &[&Comparison])
+                 -> io::Result<()> {
+    let cmps = benches.comparisons();
+    let regressions = cmps.iter().filter(|c| c.is_regression()).count();
+    let improvements = cmps.len() - regressions;
+
+    writeln!(wtr, "<h2>Summary</h2>")?;
+    writeln!(wtr, "<dl>")?;
+    writeln!(wtr, "<dt>compared</dt><dd>{}</dd>", cmps.len())?;
+    writeln!(wtr, "<dt>shown</dt><dd>{}</dd>", shown.len())?;
+    writeln!(wtr, "<dt>improvements</dt><dd>{}</dd>", improvements)?;
+    writeln!(wtr, "<dt>regressions</dt><dd>{}</dd>", regressions)?;
+    writeln!(wtr,
+             "<dt>missing in new</dt><dd>{}</dd>",
+             benches.missing_old().len())?;
+    writeln!(wtr,
+             "<dt>missing in old</dt><dd>{}</dd>",
+             benches.missing_new().len())?;
+    // The geometric mean is the appropriate average for ratios. Changes
+    // from or to 0 have no finite speedup, so they are left out of it.
+    let logs: Vec<f64> = cmps.iter()
+        .map(|c| c.speedup.ln())
+        .filter(|l| l.is_finite())
+        .collect();
+    if !logs.is_empty() {
+        let geomean = (logs.iter().sum::<f64>() / logs.len() as f64).exp();
+        writeln!(wtr, "<dt>geometric mean speedup</dt><dd>x {:.2}</dd>", geomean)?;
+    }
+    let best = cmps.iter()
+        .filter(|c| !c.is_regression() && c.diff != 0f64)
+        .max_by(|a, b| a.speedup.total_cmp(&b.speedup));
+    if let Some(best) = best {
+        writeln!(wtr,
+                 "<dt>largest improvement</dt><dd>{} ({})</dd>",
+                 escape(&best.old.name),
+                 fmt_percent(best.diff_ratio))?;
+    }
+    let worst = cmps.iter()
+        .filter(|c| c.is_regression())
+        .min_by(|a, b| a.speedup.total_cmp(&b.speedup));
+    if let Some(worst) = worst {
+        writeln!(wtr,
+                 "<dt>largest regression</dt><dd>{} ({})</dd>",
+                 escape(&worst.old.name),
+                 fmt_percent(worst.diff_ratio))?;
+    }
+    writeln!(wtr, "</dl>")?;
+    Ok(())
+}
+
+fn write_table(wtr: &mut dyn Write,
+               name_old: &str,
+               name_new: &str,
+               unit: &str,
+               shown: &[&Comparison],
+               variance: bool)
+               -> io::Result<()> {
+    writeln!(wtr, "<h2>Comparisons</h2>")?;
+    if shown.is_empty() {
+        writeln!(wtr, "<p>Nothing to show.</p>")?;
+        return Ok(());
+    }
+    writeln!(wtr, "<table class=\"sortable\">")?;
+    writeln!(wtr,
+             "<thead><tr><th>name</th><th>{old} {unit}</th><th>{new} {unit}</th>\
+              <th>diff {unit}</th><th>diff %</th><th>speedup</th></tr></thead>",
+             old = escape(name_old),
+             new = escape(name_new),
+             unit = escape(unit))?;
+    writeln!(wtr, "<tbody>")?;
+    for c in shown {
+        writeln!(wtr,
+                 "<tr class=\"{}\">\
+                  <td data-value=\"{name}\">{name}</td>\
+                  <td class=\"num\" data-value=\"{}\">{}</td>\
+                  <td class=\"num\" data-value=\"{}\">{}</td>\
+                  <td class=\"num\" data-value=\"{}\">{}</td>\
+                  <td class=\"num\" data-value=\"{}\">{}</td>\
+                  <td class=\"num\" data-value=\"{}\">x {:.2}</td>\
+                  </tr>",
+                 class(c),
+                 c.old_value(),
+                 c.old.fmt_metric(&c.metric, variance),
+                 c.new_value(),
+                 c.new.fmt_metric(&c.metric, variance),
+                 c.diff,
+                 c.fmt_diff(),
+                 c.diff_ratio,
+                 fmt_percent(c.diff_ratio),
+                 c.speedup,
+                 c.speedup,
+                 name = escape(&c.old.name))?;
+    }
+    writeln!(wtr, "</tbody>")?;
+    writeln!(wtr, "</table>")?;
+    Ok(())
+}
+
+fn write_chart(wtr: &mut dyn Write, shown: &[&Comparison]) -> io::Result<()> {
+    if shown.is_empty() {
+        return Ok(());
+    }
+    let max = shown.iter()
+        .map(|c| c.diff_ratio.abs())
+        .filter(|r| r.is_finite())
+        .fold(0f64, f64::max);
+    writeln!(wtr, "<h2>Percent change</h2>")?;
+    writeln!(wtr, "<div class=\"chart\">")?;
+    for c in shown {
+        // Each half of the track covers [0, max], so that the largest change
+        // fills its half completely. Changes from 0 get no bar.
+        let finite = c.diff_ratio.is_finite() && max > 0f64;
+        let width = if finite { c.diff_ratio.abs() / max * 50f64 } else { 0f64 };
+        let left = if c.diff_ratio < 0f64 { 50f64 - width } else { 50f64 };
+        writeln!(wtr,
+                 "<div class=\"label\">{}</div>\
+                  <div class=\"track\"><div class=\"axis\"></div>\
+                  <div class=\"bar {}\" style=\"left: {:.3}%; width: {:.3}%\"></div></div>\
+                  <div class=\"value\">{}</div>",
+                 escape(&c.old.name),
+                 class(c),
+                 left,
+                 width,
+                 fmt_percent(c.diff_ratio))?;
+    }
+    writeln!(wtr, "</div>")?;
+    Ok(())
+}
+
+fn write_missing(wtr: &mut dyn Write,
+                 name_old: &str,
+                 name_new: &str,
+                 unit: &str,
+                 benches: &PairedBenchmarks,
+                 variance: bool)
+                 -> io::Result<()> {
+    let sets = [(benches.missing_old(), name_old, name_new),
+                (benches.missing_new(), name_new, name_old)];
+    for &(missing, present, absent) in &sets {
+        if missing.is_empty() {
+            continue;
+        }
+        writeln!(wtr,
+                 "<h2>In {} but not in {}</h2>",
+                 escape(present),
+                 escape(absent))?;
+        writeln!(wtr, "<table class=\"sortable\">")?;
+        writeln!(wtr,
+                 "<thead><tr><th>name</th><th>{} {}</th></tr></thead>",
+                 escape(present),
+                 escape(unit))?;
+        writeln!(wtr, "<tbody>")?;
+        for b in missing {
+            writeln!(wtr,
+                     "<tr><td data-value=\"{name}\">{name}</td>\
+                      <td class=\"num\" data-value=\"{}\">{}</td></tr>",
+                     b.metric(benches.metric()).map_or(String::new(), |m| m.value.to_string()),
+                     b.fmt_metric(benches.metric(), variance),
+                     name = escape(&b.name))?;
+        }
+        writeln!(wtr, "</tbody>")?;
+        writeln!(wtr, "</table>")?;
+    }
+    Ok(())
+}
+
+/// Formats a relative change as a percentage, or `n/a` if it's not finite,
+/// i.e., if the old value is 0.
+fn fmt_percent(ratio: f64) -> String {
+    if ratio.is_finite() {
+        format!("{:.2}%", ratio * 100f64)
+    } else {
+        "n/a".to_string()
+    }
+}
+
+/// Returns the CSS class used to color a comparison.
+fn class(c: &Comparison) -> &'static str {
+    if c.is_regression() {
+        "regression"
+    } else {
+        "improvement"
+    }
+}
+
+/// Escapes text for inclusion in HTML element content or attribute values.
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
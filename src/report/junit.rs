@@ -0,0 +1,120 @@
+use std::io::{self, Write};
+
+use benchmark::{Benchmark, Comparison, Failure, PairedBenchmarks, Status};
+use error::Result;
+use super::{failure_message, Context, Reporter};
+use super::html::escape;
+
+/// Reports the comparisons as a JUnit XML test report.
+///
+/// Every comparison becomes a test case, which fails if it is a regression
+/// beyond `Context::threshold` (see `Comparison::fails`). The old and new
+/// timings are recorded in each test case's `system-out`. Benchmarks that
+/// are missing from either side are reported as skipped test cases, as are
+/// ignored benchmarks and those that failed in the old set. Benchmarks that
+/// failed in the new set are failed test cases.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Junit;
+
+impl Reporter for Junit {
+    fn report(&self, benches: &PairedBenchmarks, ctx: &Context, wtr: &mut dyn Write) -> Result<()> {
+        let (name_old, name_new) = (&*ctx.name_old, &*ctx.name_new);
+        let cmps = benches.comparisons();
+        let panics = benches.failures_new().iter().filter(|f| f.status == Status::Failed).count();
+        let failures = cmps.iter().filter(|c| c.fails(ctx.threshold)).count() + panics;
+        let unpaired = benches.missing_old().len() + benches.missing_new().len() +
+                       benches.failures_old().len() + benches.failures_new().len();
+        let skipped = unpaired - panics;
+
+        writeln!(wtr, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(wtr, "<testsuites>")?;
+        writeln!(wtr,
+                 "  <testsuite name=\"benchcmp: {} vs {}\" tests=\"{}\" failures=\"{}\" \
+                  errors=\"0\" skipped=\"{}\">",
+                 escape(name_old),
+                 escape(name_new),
+                 cmps.len() + unpaired,
+                 failures,
+                 skipped)?;
+        for c in cmps {
+            write_comparison(wtr, ctx, c)?;
+        }
+        for b in benches.missing_old() {
+            write_missing(wtr, b, name_new)?;
+        }
+        for b in benches.missing_new() {
+            write_missing(wtr, b, name_old)?;
+        }
+        for f in benches.failures_old() {
+            write_failure(wtr, f, name_old, false)?;
+        }
+        for f in benches.failures_new() {
+            write_failure(wtr, f, name_new, f.status == Status::Failed)?;
+        }
+        writeln!(wtr, "  </testsuite>")?;
+        writeln!(wtr, "</testsuites>")?;
+        Ok(())
+    }
+}
+
+fn write_comparison(wtr: &mut dyn Write, ctx: &Context, c: &Comparison) -> io::Result<()> {
+    writeln!(wtr,
+             "    <testcase classname=\"benchcmp\" name=\"{}\">",
+             escape(&c.old.name))?;
+    if c.fails(ctx.threshold) {
+        writeln!(wtr,
+                 "      <failure type=\"regression\" message=\"{}\"/>",
+                 escape(&failure_message(c, ctx.threshold)))?;
+    }
+    writeln!(wtr,
+             "      <system-out>{}: {}\n{}: {}\n\
+              diff: {} {} ({:.2}%, speedup x {:.2})</system-out>",
+             escape(&ctx.name_old),
+             timing(&c.old, &c.metric, &ctx.unit),
+             escape(&ctx.name_new),
+             timing(&c.new, &c.metric, &ctx.unit),
+             c.fmt_diff(),
+             escape(&ctx.unit),
+             c.diff_ratio * 100f64,
+             c.speedup)?;
+    writeln!(wtr, "    </testcase>")?;
+    Ok(())
+}
+
+fn write_missing(wtr: &mut dyn Write, b: &Benchmark, absent: &str) -> io::Result<()> {
+    writeln!(wtr,
+             "    <testcase classname=\"benchcmp\" name=\"{}\">",
+             escape(&b.name))?;
+    writeln!(wtr,
+             "      <skipped message=\"not found in {}\"/>",
+             escape(absent))?;
+    writeln!(wtr, "    </testcase>")?;
+    Ok(())
+}
+
+/// Writes a benchmark without a result in the set `name` as a failed test
+/// case if `fails` is set, and as a skipped one otherwise.
+fn write_failure(wtr: &mut dyn Write, f: &Failure, name: &str, fails: bool) -> io::Result<()> {
+    writeln!(wtr,
+             "    <testcase classname=\"benchcmp\" name=\"{}\">",
+             escape(&f.name))?;
+    let message = escape(&f.describe(name));
+    if fails {
+        writeln!(wtr, "      <failure type=\"panic\" message=\"{}\"/>", message)?;
+    } else {
+        writeln!(wtr, "      <skipped message=\"{}\"/>", message)?;
+    }
+    writeln!(wtr, "    </testcase>")?;
+    Ok(())
+}
+
+/// Formats the metric named `metric` of a benchmark in the style of
+/// libtest, with the throughput if the metric is the time.
+fn timing(b: &Benchmark, metric: &str, unit: &str) -> String {
+    let m = &b.metrics[metric];
+    let mut res = format!("{} {} (+/- {})", m.value, escape(unit), m.variance);
+    if let (true, Some(throughput)) = (metric == "time", b.metric("throughput")) {
+        res = format!("{} = {} {}", res, throughput.value, escape(&throughput.unit));
+    }
+    res
+}
@@ -0,0 +1,144 @@
+/*!
+Reporters render paired benchmarks in some output format.
+
+Every format supported by `cargo benchcmp` is implemented as a
+[`Reporter`](trait.Reporter.html), and custom formats can be added by
+implementing the trait. A reporter receives the
+[`PairedBenchmarks`](../struct.PairedBenchmarks.html) to show, along with a
+[`Context`](struct.Context.html) that carries the names of both sides and the
+user's filtering options, and writes its output to any `io::Write`.
+
+# Example
+
+A reporter that lists the names of the regressed benchmarks:
+
+```
+extern crate benchcmp;
+
+use std::io::Write;
+
+use benchcmp::{Benchmarks, Context, PairedBenchmarks, Reporter, Result};
+
+struct Names;
+
+impl Reporter for Names {
+    fn report(&self, benches: &PairedBenchmarks, ctx: &Context, wtr: &mut dyn Write) -> Result<()> {
+        for c in ctx.shown(benches) {
+            writeln!(wtr, "{}", c.old.name)?;
+        }
+        Ok(())
+    }
+}
+
+# fn main() {
+let old = benchcmp::parse_buffer(&b"test fib ... bench: 900 ns/iter (+/- 35)"[..]).unwrap();
+let new = benchcmp::parse_buffer(&b"test fib ... bench: 1,200 ns/iter (+/- 40)"[..]).unwrap();
+let paired = Benchmarks::from((old, new)).paired();
+
+let mut ctx = Context::new("old", "new");
+ctx.only_regressions = true;
+let mut out = vec![];
+Names.report(&paired, &ctx, &mut out).unwrap();
+assert_eq!(out, b"fib\n");
+# }
+```
+*/
+
+use std::io::Write;
+
+use benchmark::{Comparison, PairedBenchmarks};
+use error::Result;
+
+pub use self::github::Github;
+pub use self::html::Html;
+pub use self::junit::Junit;
+pub use self::svg::Svg;
+pub use self::table::Table;
+pub use self::tap::Tap;
+
+mod bars;
+mod github;
+mod html;
+mod junit;
+mod svg;
+mod table;
+mod tap;
+
+/// Renders paired benchmarks in some output format.
+pub trait Reporter {
+    /// Writes a report of `benches` to `wtr`.
+    fn report(&self, benches: &PairedBenchmarks, ctx: &Context, wtr: &mut dyn Write) -> Result<()>;
+}
+
+/// The options a report is rendered with.
+///
+/// Build one with `Context::new` and adjust its fields as needed.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Context {
+    /// The name of the old set of benchmarks, e.g., for column headers.
+    pub name_old: String,
+    /// The name of the new set of benchmarks, e.g., for column headers.
+    pub name_new: String,
+    /// The unit of the metric the benchmarks are compared by, `ns/iter` for
+    /// their time (see `Benchmarks::select_metric` and
+    /// `PairedBenchmarks::unit`).
+    pub unit: String,
+    /// Only show comparisons whose percent change is at least this large.
+    ///
+    /// Formats that report regressions as failures instead use it as the
+    /// smallest regression that fails (see `Comparison::fails`).
+    pub threshold: Option<u8>,
+    /// Only show improvements.
+    pub only_improvements: bool,
+    /// Only show regressions.
+    pub only_regressions: bool,
+    /// Show the variance of each benchmark.
+    pub variance: bool,
+    /// Show benchmarks that are missing from either side alongside the
+    /// comparisons.
+    pub include_missing: bool,
+    /// The metrics to show, by name (see `Benchmark::metric`), or none to
+    /// only show the metric the benchmarks are compared by. Only the table
+    /// shows them.
+    pub metrics: Vec<String>,
+}
+
+impl Context {
+    /// Creates a context with the given names that shows everything.
+    pub fn new(name_old: &str, name_new: &str) -> Context {
+        Context {
+            name_old: name_old.to_string(),
+            name_new: name_new.to_string(),
+            unit: "ns/iter".to_string(),
+            threshold: None,
+            only_improvements: false,
+            only_regressions: false,
+            variance: false,
+            include_missing: false,
+            metrics: vec![],
+        }
+    }
+
+    /// Returns true if the given comparison passes the threshold,
+    /// improvements and regressions filters.
+    pub fn is_shown(&self, c: &Comparison) -> bool {
+        let regression = c.is_regression();
+        !(self.threshold.is_some_and(|t| c.abs_percent() < t) ||
+          self.only_regressions && !regression ||
+          self.only_improvements && regression)
+    }
+
+    /// Returns the comparisons in `benches` that pass the filters.
+    pub fn shown<'a>(&self, benches: &'a PairedBenchmarks) -> Vec<&'a Comparison> {
+        benches.comparisons().iter().filter(|c| self.is_shown(c)).collect()
+    }
+}
+
+/// Describes why a comparison fails, for formats that report failures.
+fn failure_message(c: &Comparison, threshold: Option<u8>) -> String {
+    match threshold {
+        None => format!("regressed by {:.2}%", c.diff_ratio * 100f64),
+        Some(t) => format!("regressed by {:.2}% (threshold: {}%)", c.diff_ratio * 100f64, t),
+    }
+}
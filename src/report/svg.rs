@@ -0,0 +1,152 @@
+use std::io::{self, Write};
+
+use benchmark::{Comparison, PairedBenchmarks};
+use error::Result;
+use super::{Context, Reporter};
+use super::html::escape;
+
+/// The height of each bar's row, in pixels.
+const ROW_HEIGHT: f64 = 20.0;
+/// The height of each bar, in pixels.
+const BAR_HEIGHT: f64 = 14.0;
+/// The width of the plotting area (both halves), in pixels.
+const PLOT_WIDTH: f64 = 480.0;
+/// An approximation of the width of one character of the label font.
+const CHAR_WIDTH: f64 = 7.2;
+/// Whitespace around the chart and between its columns, in pixels.
+const PADDING: f64 = 10.0;
+/// The space reserved for the header line, in pixels.
+const HEADER_HEIGHT: f64 = 30.0;
+/// The space reserved for the percentage labels right of the plot, in pixels.
+const VALUE_WIDTH: f64 = 70.0;
+
+/// Renders a horizontal bar chart of the percent change of each comparison
+/// as a standalone SVG image.
+///
+/// Decreases are drawn to the left of the axis and increases to the right
+/// of it, with improvements in green and regressions in red. The variance
+/// of both measurements is drawn as an error bar around the end of each
+/// bar. Only comparisons that pass the context's filters are drawn.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Svg;
+
+impl Reporter for Svg {
+    fn report(&self, benches: &PairedBenchmarks, ctx: &Context, wtr: &mut dyn Write) -> Result<()> {
+        write_chart(wtr, &ctx.name_old, &ctx.name_new, &ctx.shown(benches))?;
+        Ok(())
+    }
+}
+
+fn write_chart(wtr: &mut dyn Write,
+               name_old: &str,
+               name_new: &str,
+               shown: &[&Comparison])
+               -> io::Result<()> {
+    let label_width = shown.iter()
+        .map(|c| c.old.name.chars().count())
+        .max()
+        .unwrap_or(0) as f64 * CHAR_WIDTH;
+    let plot_left = PADDING + label_width + PADDING;
+    let axis = plot_left + PLOT_WIDTH / 2.0;
+    let width = plot_left + PLOT_WIDTH + PADDING + VALUE_WIDTH;
+    let height = HEADER_HEIGHT + ROW_HEIGHT * shown.len() as f64 + PADDING;
+    // Scale so that the longest bar, including its error bar, fits in its
+    // half of the plot. Changes from 0 have no finite length, so they get a
+    // label instead of a bar.
+    let max = shown.iter()
+        .filter(|c| c.diff_ratio.is_finite())
+        .map(|c| c.diff_ratio.abs() + error_ratio(c))
+        .fold(0f64, f64::max);
+    let scale = if max > 0.0 { PLOT_WIDTH / 2.0 / max } else { 0.0 };
+
+    writeln!(wtr,
+             "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" \
+              viewBox=\"0 0 {w:.0} {h:.0}\" font-family=\"monospace\" font-size=\"12\">",
+             w = width,
+             h = height)?;
+    writeln!(wtr,
+             "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+    writeln!(wtr,
+             "<text x=\"{:.1}\" y=\"{:.1}\" font-weight=\"bold\">\
+              diff % ({} vs {})</text>",
+             PADDING,
+             HEADER_HEIGHT / 2.0 + 4.0,
+             escape(name_old),
+             escape(name_new))?;
+    for (i, c) in shown.iter().enumerate() {
+        let top = HEADER_HEIGHT + ROW_HEIGHT * i as f64;
+        let middle = top + ROW_HEIGHT / 2.0;
+        let color = if c.is_regression() { "#d55" } else { "#5a5" };
+        writeln!(wtr,
+                 "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+                 plot_left - PADDING,
+                 middle + 4.0,
+                 escape(&c.old.name))?;
+        if !c.diff_ratio.is_finite() {
+            writeln!(wtr,
+                     "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\">old value is 0</text>",
+                     axis + PADDING / 2.0,
+                     middle + 4.0,
+                     color)?;
+            writeln!(wtr,
+                     "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">n/a</text>",
+                     width - PADDING,
+                     middle + 4.0)?;
+            continue;
+        }
+        let len = c.diff_ratio.abs() * scale;
+        let x = if c.diff_ratio < 0.0 { axis - len } else { axis };
+        writeln!(wtr,
+                 "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" \
+                  fill=\"{}\"><title>{}: {:.2}%</title></rect>",
+                 x,
+                 middle - BAR_HEIGHT / 2.0,
+                 len,
+                 BAR_HEIGHT,
+                 color,
+                 escape(&c.old.name),
+                 c.diff_ratio * 100.0)?;
+
+        let end = axis + c.diff_ratio * scale;
+        let err = error_ratio(c) * scale;
+        if err > 0.0 {
+            let (lo, hi) = (end - err, end + err);
+            let (cap_top, cap_bottom) = (middle - BAR_HEIGHT / 4.0, middle + BAR_HEIGHT / 4.0);
+            writeln!(wtr,
+                     "<path d=\"M{lo:.1} {m:.1}H{hi:.1}M{lo:.1} {t:.1}V{b:.1}M{hi:.1} {t:.1}V{b:.1}\" \
+                      stroke=\"#333\" fill=\"none\"/>",
+                     lo = lo,
+                     hi = hi,
+                     m = middle,
+                     t = cap_top,
+                     b = cap_bottom)?;
+        }
+        writeln!(wtr,
+                 "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{:.2}%</text>",
+                 width - PADDING,
+                 middle + 4.0,
+                 c.diff_ratio * 100.0)?;
+    }
+    writeln!(wtr,
+             "<line x1=\"{x:.1}\" y1=\"{:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\" stroke=\"#888\"/>",
+             HEADER_HEIGHT,
+             height - PADDING,
+             x = axis)?;
+    writeln!(wtr, "</svg>")?;
+    Ok(())
+}
+
+/// Returns the uncertainty of the comparison's `diff_ratio`, derived from
+/// the variance reported for each measurement.
+///
+/// The relative errors of the old and new measurements are combined in
+/// quadrature, which is the usual propagation of error for a quotient.
+fn error_ratio(c: &Comparison) -> f64 {
+    let (old, new) = (&c.old.metrics[&c.metric], &c.new.metrics[&c.metric]);
+    if old.value == 0.0 || new.value == 0.0 {
+        return 0.0;
+    }
+    let rel_old = old.variance / old.value;
+    let rel_new = new.variance / new.value;
+    (new.value / old.value) * (rel_old * rel_old + rel_new * rel_new).sqrt()
+}
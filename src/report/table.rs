@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use prettytable::{self, Row};
+use prettytable::format;
+use term::{Terminal, TerminfoTerminal};
+
+use benchmark::{Benchmark, Comparison, PairedBenchmarks};
+use error::Result;
+use scaling::Family;
+use super::{bars, Context, Reporter};
+
+/// Renders the comparisons as a plain text table.
+///
+/// This is the default output of `cargo benchcmp`. If no benchmarks could
+/// be paired, or none of them pass the context's filters, nothing is
+/// written.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Table {
+    /// Color improvements green and regressions red, using the escape codes
+    /// of the terminal described by `$TERM`.
+    pub color: bool,
+    /// Add a column with a bar proportional to each percent change.
+    pub bars: bool,
+    /// Draw bars with Unicode block characters instead of ASCII.
+    pub unicode: bool,
+}
+
+impl Table {
+    /// Creates a table reporter without colors or bars.
+    ///
+    /// Whether bars may use Unicode is determined from the locale.
+    pub fn new() -> Table {
+        Table {
+            color: false,
+            bars: false,
+            unicode: bars::unicode_supported(),
+        }
+    }
+}
+
+/// What a set of benchmarks has for one row of a table with several sets.
+#[derive(Clone, Copy)]
+enum Entry<'a> {
+    Missing,
+    Compared(&'a Comparison),
+    /// The set has the benchmark, but the baseline doesn't.
+    Unpaired(&'a Benchmark),
+}
+
+impl Table {
+    /// Renders several sets compared against the same baseline as one
+    /// table.
+    ///
+    /// Each element of `sets` is the name of a set and its benchmarks
+    /// paired with those of the baseline, which is named by
+    /// `Context::name_old`. The table has a column with the measurements of
+    /// the baseline, then two columns per set: its measurements and their
+    /// percent change. A row is shown if any of its comparisons passes the
+    /// context's filters. Bars and additional metrics aren't shown.
+    pub fn report_sets(&self,
+                       sets: &[(String, PairedBenchmarks)],
+                       ctx: &Context,
+                       wtr: &mut dyn Write)
+                       -> Result<()> {
+        let mut rows: BTreeMap<&str, (Option<&Benchmark>, Vec<Entry>)> = BTreeMap::new();
+        let empty = || (None, vec![Entry::Missing; sets.len()]);
+        for (i, (_, benches)) in sets.iter().enumerate() {
+            for c in benches.comparisons() {
+                let row = rows.entry(&c.old.name).or_insert_with(empty);
+                row.0 = Some(&c.old);
+                row.1[i] = Entry::Compared(c);
+            }
+            if ctx.include_missing {
+                for b in benches.missing_old() {
+                    rows.entry(&b.name).or_insert_with(empty).0 = Some(b);
+                }
+                for b in benches.missing_new() {
+                    rows.entry(&b.name).or_insert_with(empty).1[i] = Entry::Unpaired(b);
+                }
+            }
+        }
+
+        let mut output = prettytable::Table::new();
+        output.set_format(*format::consts::FORMAT_CLEAN);
+        let mut header = row![b->"name", b->format!("{} {}", ctx.name_old, ctx.unit)];
+        for (name, _) in sets {
+            header.add_cell(cell!(b->format!("{} {}", name, ctx.unit)));
+            header.add_cell(cell!(br->format!("{} diff %", name)));
+        }
+        output.add_row(header);
+        for (name, (base, entries)) in rows {
+            let compared: Vec<&Comparison> = entries.iter()
+                .filter_map(|e| if let Entry::Compared(c) = *e { Some(c) } else { None })
+                .collect();
+            if !compared.is_empty() && !compared.iter().any(|c| ctx.is_shown(c)) {
+                continue;
+            }
+            let metric = sets[0].1.metric();
+            let mut row = row![name, base.map_or("n/a".to_string(), |b| b.fmt_metric(metric, ctx.variance))];
+            for entry in entries {
+                match entry {
+                    Entry::Missing => {
+                        row.add_cell(cell!("n/a"));
+                        row.add_cell(cell!(r->"n/a"));
+                    }
+                    Entry::Unpaired(b) => {
+                        row.add_cell(cell!(b.fmt_metric(metric, ctx.variance)));
+                        row.add_cell(cell!(r->"n/a"));
+                    }
+                    Entry::Compared(c) => {
+                        let ns = c.new.fmt_metric(metric, ctx.variance);
+                        let diff_ratio = format!("{:.2}%", c.diff_ratio * 100f64);
+                        if c.is_regression() {
+                            row.add_cell(cell!(Fr->ns));
+                            row.add_cell(cell!(rFr->diff_ratio));
+                        } else {
+                            row.add_cell(cell!(Fg->ns));
+                            row.add_cell(cell!(rFg->diff_ratio));
+                        }
+                    }
+                }
+            }
+            output.add_row(row);
+        }
+        self.print(&output, wtr)
+    }
+
+    /// Renders families of parameterized benchmarks, with an estimate of how
+    /// their results scale with the parameter.
+    ///
+    /// Each family has a row per benchmark, with its parameter, its old and
+    /// new measurements and their percent change, followed by a `fit` row
+    /// with the estimated exponent of the parameter in the old and new
+    /// results (see `Family::exponent_old`), and in their ratio. Families
+    /// are shown in full, regardless of the context's filters.
+    pub fn report_scaling(&self,
+                          families: &[Family],
+                          ctx: &Context,
+                          wtr: &mut dyn Write)
+                          -> Result<()> {
+        let mut output = prettytable::Table::new();
+        output.set_format(*format::consts::FORMAT_CLEAN);
+        output.add_row(row![
+            b->"family",
+            br->"param",
+            b->format!("{} {}", ctx.name_old, ctx.unit),
+            b->format!("{} {}", ctx.name_new, ctx.unit),
+            br->"diff %"
+        ]);
+        let exponent = |k: Option<f64>, sign: bool| match k {
+            // Don't show a sign for exponents that round to zero.
+            Some(k) if sign && k.abs() < 0.005 => "n^0.00".to_string(),
+            Some(k) if sign => format!("n^{:+.2}", k),
+            Some(k) => format!("n^{:.2}", k),
+            None => "n/a".to_string(),
+        };
+        for family in families {
+            for &(param, c) in &family.points {
+                let diff_ratio = format!("{:.2}%", c.diff_ratio * 100f64);
+                output.add_row(row![
+                    family.name,
+                    r->param,
+                    c.old.fmt_metric(&c.metric, ctx.variance),
+                    c.new.fmt_metric(&c.metric, ctx.variance),
+                    if c.is_regression() { cell!(rFr->diff_ratio) } else { cell!(rFg->diff_ratio) }
+                ]);
+            }
+            let ratio = family.exponent_ratio();
+            let ratio_cell = match ratio {
+                Some(k) if k >= 0.005 => cell!(rFr->exponent(ratio, true)),
+                Some(_) => cell!(rFg->exponent(ratio, true)),
+                None => cell!(r->exponent(ratio, true)),
+            };
+            output.add_row(row![
+                family.name,
+                r->"fit",
+                exponent(family.exponent_old(), false),
+                exponent(family.exponent_new(), false),
+                ratio_cell
+            ]);
+        }
+        self.print(&output, wtr)
+    }
+
+    /// Writes `output` unless it only has a header, in color if enabled.
+    fn print(&self, output: &prettytable::Table, wtr: &mut dyn Write) -> Result<()> {
+        if output.len() <= 1 {
+            return Ok(());
+        }
+        // Terminals need `Send` writers, so render colors into a buffer.
+        match TerminfoTerminal::new(vec![]) {
+            Some(mut term) if self.color => {
+                output.print_term(&mut term)?;
+                wtr.write_all(term.get_ref())?;
+            }
+            _ => {
+                output.print(wtr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Table {
+    fn default() -> Table {
+        Table::new()
+    }
+}
+
+impl Reporter for Table {
+    fn report(&self, benches: &PairedBenchmarks, ctx: &Context, wtr: &mut dyn Write) -> Result<()> {
+        if benches.comparisons().is_empty() {
+            return Ok(());
+        }
+        let mut output = prettytable::Table::new();
+        output.set_format(*format::consts::FORMAT_CLEAN);
+        if ctx.metrics.is_empty() {
+            output.add_row(row![
+                b->"name",
+                b->format!("{} {}", ctx.name_old, ctx.unit),
+                b->format!("{} {}", ctx.name_new, ctx.unit),
+                br->format!("diff {}", ctx.unit),
+                br->"diff %",
+                br->"speedup"
+            ]);
+        } else {
+            let mut header = row![b->"name"];
+            for name in &ctx.metrics {
+                // Counters are their own unit, which needn't be repeated.
+                let unit = metric_unit(benches, name)
+                    .filter(|&unit| unit != name)
+                    .map_or(String::new(), |unit| format!(" {}", unit));
+                header.add_cell(cell!(b->format!("{} {}{}", ctx.name_old, name, unit)));
+                header.add_cell(cell!(b->format!("{} {}{}", ctx.name_new, name, unit)));
+                header.add_cell(cell!(br->format!("{} diff %", name)));
+            }
+            output.add_row(header);
+        }
+        let shown = ctx.shown(benches);
+        let max = shown.iter()
+            .map(|c| c.diff_ratio.abs())
+            .filter(|r| r.is_finite())
+            .fold(0f64, f64::max);
+        if self.bars {
+            output.get_mut_row(0).unwrap().add_cell(cell!(b->"diff bar"));
+        }
+        for c in shown {
+            let regression = c.is_regression();
+            let mut row = if ctx.metrics.is_empty() {
+                to_row(c, ctx.variance, regression)
+            } else {
+                metrics_row(c, &ctx.metrics, ctx.variance, regression)
+            };
+            if self.bars {
+                let bar = bars::bar(c.diff_ratio, max, self.unicode);
+                row.add_cell(if regression { cell!(Fr->bar) } else { cell!(Fg->bar) });
+            }
+            output.add_row(row);
+        }
+
+        if ctx.include_missing {
+            let metrics = if ctx.metrics.is_empty() {
+                vec![benches.metric().to_string()]
+            } else {
+                ctx.metrics.clone()
+            };
+            // Without `--metrics`, the table has diff and speedup columns
+            // instead of a diff % column per metric.
+            let blank = if ctx.metrics.is_empty() { 2 } else { 1 };
+            let columns = output.get_row(0).map_or(0, Row::len);
+            for (b, old) in benches.missing_old().iter().map(|b| (b, true))
+                .chain(benches.missing_new().iter().map(|b| (b, false))) {
+                let mut row = row![b.name];
+                for name in &metrics {
+                    let value = b.fmt_metric(name, ctx.variance);
+                    if old {
+                        row.add_cell(cell!(value));
+                        row.add_cell(cell!("n/a"));
+                    } else {
+                        row.add_cell(cell!("n/a"));
+                        row.add_cell(cell!(value));
+                    }
+                    for _ in 0..blank {
+                        row.add_cell(cell!(r->"n/a"));
+                    }
+                }
+                // Leave the remaining columns, like the speedup and the bar,
+                // empty.
+                while row.len() < columns {
+                    row.add_cell(cell!(""));
+                }
+                output.add_row(row);
+            }
+        }
+
+        self.print(&output, wtr)
+    }
+}
+
+/// Returns the unit of the metric named `name`, from the first comparison
+/// that has it.
+fn metric_unit<'a>(benches: &'a PairedBenchmarks, name: &str) -> Option<&'a str> {
+    benches.comparisons().iter().filter_map(|c| c.old.metric(name)).map(|m| &*m.unit).next()
+}
+
+/// Convert a comparison to a row with the old and new value and the percent
+/// change of each of the given metrics, or `n/a` where either benchmark
+/// lacks one. The name is colored by the comparison, and the values of each
+/// metric by their own change.
+fn metrics_row(c: &Comparison, metrics: &[String], variance: bool, regression: bool) -> Row {
+    let mut row = if regression { row![Fr->c.old.name] } else { row![Fg->c.old.name] };
+    for name in metrics {
+        match c.metric(name) {
+            None => {
+                row.add_cell(cell!(c.old.fmt_metric(name, variance)));
+                row.add_cell(cell!(c.new.fmt_metric(name, variance)));
+                row.add_cell(cell!(r->"n/a"));
+            }
+            Some(m) => {
+                let (old, new, percent) = (m.old.fmt(variance), m.new.fmt(variance), m.fmt_percent());
+                if m.is_regression() {
+                    row.add_cell(cell!(Fr->old));
+                    row.add_cell(cell!(Fr->new));
+                    row.add_cell(cell!(rFr->percent));
+                } else {
+                    row.add_cell(cell!(Fg->old));
+                    row.add_cell(cell!(Fg->new));
+                    row.add_cell(cell!(rFg->percent));
+                }
+            }
+        }
+    }
+    row
+}
+
+/// Convert a comparison to a formatted row useful for printing.
+///
+/// The columns of the row are as follows: the name of the benchmark being
+/// compared, the old measurement, the new measurement, the measurement
+/// difference and the percent measurement difference. Negative differences
+/// imply an improvement in performance from old to new.
+fn to_row(c: &Comparison, variance: bool, regression: bool) -> Row {
+    let name = &c.old.name;
+    let fst_ns = c.old.fmt_metric(&c.metric, variance);
+    let snd_ns = c.new.fmt_metric(&c.metric, variance);
+    let diff_ratio = format!("{:.2}%", c.diff_ratio * 100f64);
+    let speedup = format!("x {:.2}", c.speedup);
+    let diff_ns = c.fmt_diff();
+    if regression {
+        row![Fr->name, Fr->fst_ns, Fr->snd_ns, rFr->diff_ns, rFr->diff_ratio, rFr->speedup]
+    } else {
+        row![Fg->name, Fg->fst_ns, Fg->snd_ns, rFg->diff_ns, rFg->diff_ratio, rFg->speedup]
+    }
+}
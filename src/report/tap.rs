@@ -0,0 +1,156 @@
+use std::io::{self, Write};
+
+use benchmark::{Benchmark, Comparison, Failure, PairedBenchmarks, Status};
+use error::Result;
+use super::{failure_message, Context, Reporter};
+
+/// Reports the comparisons as a TAP (version 13) stream.
+///
+/// Every comparison is one test point, which is `not ok` if it is a
+/// regression beyond `Context::threshold` (see `Comparison::fails`). Each
+/// test point is followed by a YAML diagnostics block with its
+/// measurements. Benchmarks that are missing from either side are reported
+/// as skipped, as are ignored benchmarks and those that failed in the old
+/// set. Benchmarks that failed in the new set are `not ok`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tap;
+
+impl Reporter for Tap {
+    fn report(&self, benches: &PairedBenchmarks, ctx: &Context, wtr: &mut dyn Write) -> Result<()> {
+        let cmps = benches.comparisons();
+        let total = cmps.len() + benches.missing_old().len() + benches.missing_new().len() +
+                    benches.failures_old().len() + benches.failures_new().len();
+
+        writeln!(wtr, "TAP version 13")?;
+        writeln!(wtr, "1..{}", total)?;
+        let mut number = 0;
+        for c in cmps {
+            number += 1;
+            write_comparison(wtr, number, c, ctx.threshold)?;
+        }
+        for b in benches.missing_old() {
+            number += 1;
+            write_missing(wtr, number, b, &ctx.name_new)?;
+        }
+        for b in benches.missing_new() {
+            number += 1;
+            write_missing(wtr, number, b, &ctx.name_old)?;
+        }
+        for f in benches.failures_old() {
+            number += 1;
+            write_failure(wtr, number, f, &ctx.name_old, false)?;
+        }
+        for f in benches.failures_new() {
+            number += 1;
+            write_failure(wtr, number, f, &ctx.name_new, f.status == Status::Failed)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_comparison(wtr: &mut dyn Write,
+                    number: usize,
+                    c: &Comparison,
+                    threshold: Option<u8>)
+                    -> io::Result<()> {
+    let fails = c.fails(threshold);
+    writeln!(wtr,
+             "{} {} - {}",
+             if fails { "not ok" } else { "ok" },
+             number,
+             escape_description(&c.old.name))?;
+    writeln!(wtr, "  ---")?;
+    if fails {
+        writeln!(wtr, "  message: {}", yaml_string(&failure_message(c, threshold)))?;
+        writeln!(wtr, "  severity: fail")?;
+    }
+    let key = measurement_key(&c.metric);
+    writeln!(wtr, "  old:")?;
+    write_measurements(wtr, &c.old, &c.metric)?;
+    writeln!(wtr, "  new:")?;
+    write_measurements(wtr, &c.new, &c.metric)?;
+    writeln!(wtr, "  diff_{}: {}", key, c.diff)?;
+    writeln!(wtr, "  diff_percent: {:.2}", c.diff_ratio * 100f64)?;
+    writeln!(wtr, "  speedup: {:.2}", c.speedup)?;
+    writeln!(wtr, "  ...")?;
+    Ok(())
+}
+
+/// Returns the YAML key of the compared metric, which is `ns` for the time.
+fn measurement_key(metric: &str) -> &str {
+    if metric == "time" { "ns" } else { metric }
+}
+
+fn write_measurements(wtr: &mut dyn Write, b: &Benchmark, metric: &str) -> io::Result<()> {
+    let m = &b.metrics[metric];
+    writeln!(wtr, "    {}: {}", measurement_key(metric), m.value)?;
+    writeln!(wtr, "    variance: {}", m.variance)?;
+    if let (true, Some(throughput)) = (metric == "time", b.metric("throughput")) {
+        writeln!(wtr, "    mb_per_s: {}", throughput.value)?;
+    }
+    Ok(())
+}
+
+fn write_missing(wtr: &mut dyn Write,
+                 number: usize,
+                 b: &Benchmark,
+                 absent: &str)
+                 -> io::Result<()> {
+    writeln!(wtr,
+             "ok {} - {} # SKIP not found in {}",
+             number,
+             escape_description(&b.name),
+             escape_description(absent))
+}
+
+/// Writes a benchmark without a result in the set `name` as a test point
+/// that is `not ok` if `fails` is set, and skipped otherwise.
+///
+/// A directive can't span lines, so a skipped benchmark's reason only
+/// contains the first line of its panic message.
+fn write_failure(wtr: &mut dyn Write,
+                 number: usize,
+                 f: &Failure,
+                 name: &str,
+                 fails: bool)
+                 -> io::Result<()> {
+    let message = f.describe(name);
+    if !fails {
+        return writeln!(wtr,
+                        "ok {} - {} # SKIP {}",
+                        number,
+                        escape_description(&f.name),
+                        escape_description(message.lines().next().unwrap_or("")));
+    }
+    writeln!(wtr, "not ok {} - {}", number, escape_description(&f.name))?;
+    writeln!(wtr, "  ---")?;
+    writeln!(wtr, "  message: {}", yaml_string(&message))?;
+    writeln!(wtr, "  severity: fail")?;
+    writeln!(wtr, "  ...")?;
+    Ok(())
+}
+
+/// Escapes a test point description, in which `#` starts a directive.
+fn escape_description(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('#', "\\#")
+}
+
+/// Formats a string as a double quoted YAML scalar, which keeps it on one
+/// line by escaping line breaks and other control characters.
+fn yaml_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
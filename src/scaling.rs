@@ -0,0 +1,137 @@
+/*!
+Scaling analysis of parameterized benchmark families.
+
+Benchmarks such as `sort_10`, `sort_100` and `sort_1000` run the same code on
+inputs of different sizes. [`families`](fn.families.html) groups their
+comparisons into a [`Family`](struct.Family.html), which estimates how the
+old and new results grow with the parameter. A change that helps large
+inputs but hurts small ones shows up as a higher exponent in the new results.
+*/
+
+use std::collections::BTreeMap;
+
+use regex::Regex;
+
+use benchmark::{Comparison, PairedBenchmarks};
+
+/// The comparisons of benchmarks that only differ by a numeric parameter.
+#[derive(Clone, Debug)]
+pub struct Family<'a> {
+    /// The name of the family, which is the name of its benchmarks with the
+    /// parameter removed.
+    pub name: String,
+    /// The parameter of each benchmark and its comparison, sorted by
+    /// parameter.
+    pub points: Vec<(f64, &'a Comparison)>,
+}
+
+impl<'a> Family<'a> {
+    /// Estimates the exponent `k` such that the old results grow as
+    /// `param^k`, e.g., 1 for linear and 2 for quadratic growth.
+    ///
+    /// Returns `None` if the family has fewer than two distinct positive
+    /// parameters with a positive result.
+    pub fn exponent_old(&self) -> Option<f64> {
+        fit_exponent(self.points.iter().map(|&(param, c)| (param, c.old_value())))
+    }
+
+    /// Estimates the exponent `k` such that the new results grow as
+    /// `param^k`. See `exponent_old`.
+    pub fn exponent_new(&self) -> Option<f64> {
+        fit_exponent(self.points.iter().map(|&(param, c)| (param, c.new_value())))
+    }
+
+    /// Estimates the exponent `k` such that the ratio of the new results to
+    /// the old ones grows as `param^k`.
+    ///
+    /// A positive exponent means that the new results scale worse than the
+    /// old ones, so the change is relatively better for small parameters.
+    pub fn exponent_ratio(&self) -> Option<f64> {
+        fit_exponent(self.points.iter().map(|&(param, c)| (param, c.new_value() / c.old_value())))
+    }
+}
+
+/// Groups the comparisons of parameterized benchmarks into families.
+///
+/// `re` finds the parameter in the name of each benchmark, with a capture
+/// group named `param`, e.g., `_(?P<param>\d+)$` for `sort_1000`. The whole
+/// match is removed from the name to get the name of its family. Benchmarks
+/// that `re` doesn't match, or whose parameter isn't a number, are left out.
+/// The families are sorted by name.
+pub fn families<'a>(benches: &'a PairedBenchmarks, re: &Regex) -> Vec<Family<'a>> {
+    let mut families: BTreeMap<String, Vec<(f64, &Comparison)>> = BTreeMap::new();
+    for c in benches.comparisons() {
+        let caps = match re.captures(&c.old.name) {
+            Some(caps) => caps,
+            None => continue,
+        };
+        let param = match caps.name("param").and_then(|m| m.as_str().parse().ok()) {
+            Some(param) => param,
+            None => continue,
+        };
+        let whole = caps.get(0).unwrap();
+        let name = format!("{}{}", &c.old.name[..whole.start()], &c.old.name[whole.end()..]);
+        families.entry(name).or_default().push((param, c));
+    }
+    families.into_iter()
+        .map(|(name, mut points)| {
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            Family { name, points }
+        })
+        .collect()
+}
+
+/// Fits `y = a * x^k` to the points by least squares on a log-log scale and
+/// returns `k`. Points that can't be on a log scale are ignored.
+fn fit_exponent<I: Iterator<Item = (f64, f64)>>(points: I) -> Option<f64> {
+    let logs: Vec<(f64, f64)> = points.filter(|&(x, y)| x > 0.0 && y > 0.0 && x.is_finite() && y.is_finite())
+        .map(|(x, y)| (x.ln(), y.ln()))
+        .collect();
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = logs.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = logs.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    if logs.len() < 2 || sxx == 0.0 {
+        return None;
+    }
+    Some(sxy / sxx)
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use benchmark::{bench, Benchmarks};
+    use super::{families, fit_exponent};
+
+    #[test]
+    fn exponents() {
+        assert_eq!(fit_exponent(vec![(10.0, 7.0)].into_iter()), None);
+        assert_eq!(fit_exponent(vec![(10.0, 7.0), (10.0, 9.0)].into_iter()), None);
+        let quadratic = vec![(10.0, 300.0), (100.0, 30_000.0), (1000.0, 3_000_000.0)];
+        assert!((fit_exponent(quadratic.into_iter()).unwrap() - 2.0).abs() < 1e-9);
+        let constant = vec![(1.0, 5.0), (0.0, 1.0), (2.0, 5.0), (3.0, 1.0 / 0.0)];
+        assert!(fit_exponent(constant.into_iter()).unwrap().abs() < 1e-9);
+    }
+
+    #[test]
+    fn grouped() {
+        let old = vec![bench("sort_1000", 10_000), bench("sort_10", 100), bench("sort_100", 1_000),
+                       bench("search_10/hit", 50), bench("search_100/hit", 60), bench("setup", 5)];
+        let new = vec![bench("sort_10", 10), bench("sort_100", 1_000), bench("sort_1000", 100_000),
+                       bench("search_10/hit", 50), bench("search_100/hit", 60), bench("setup", 5)];
+        let paired = Benchmarks::from((old, new)).paired();
+        let re = Regex::new(r"_(?P<param>\d+)").unwrap();
+        let families = families(&paired, &re);
+
+        let names: Vec<&str> = families.iter().map(|f| &*f.name).collect();
+        assert_eq!(names, vec!["search/hit", "sort"]);
+        let params: Vec<f64> = families[1].points.iter().map(|p| p.0).collect();
+        assert_eq!(params, vec![10.0, 100.0, 1000.0]);
+        assert!((families[1].exponent_old().unwrap() - 1.0).abs() < 1e-9);
+        assert!((families[1].exponent_new().unwrap() - 2.0).abs() < 1e-9);
+        assert!((families[1].exponent_ratio().unwrap() - 1.0).abs() < 1e-9);
+        assert!(families[0].exponent_ratio().unwrap().abs() < 1e-9);
+    }
+}
@@ -0,0 +1,461 @@
+//! The interactive terminal UI of `--tui`.
+
+use std::cmp::Ordering;
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use benchcmp::{Benchmark, Comparison, Context, PairedBenchmarks};
+
+/// The help shown in the status line.
+const KEYS: &str = "/ filter  1-6 sort  r reverse  v variance columns  t throughput columns  q quit";
+
+/// The number of lines of the detail pane, including its borders.
+const DETAIL_HEIGHT: u16 = 9;
+
+/// Shows the comparisons that pass the context's filters in an interactive
+/// table until the user quits.
+pub fn run(benches: &PairedBenchmarks, ctx: &Context) -> io::Result<()> {
+    let mut app = App::new(benches, ctx);
+    let mut terminal = ratatui::try_init()?;
+    let result = app.run(&mut terminal);
+    let restored = ratatui::try_restore();
+    result.and(restored)
+}
+
+/// A main column of the table, which the rows can be sorted by.
+///
+/// The columns with the variance and throughput of each side, which can be
+/// toggled, follow the `Old` and `New` columns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Column {
+    Name,
+    Old,
+    New,
+    Diff,
+    DiffRatio,
+    Speedup,
+}
+
+impl Column {
+    /// All main columns, in the order they're shown and numbered for
+    /// sorting.
+    const ALL: [Column; 6] = [Column::Name,
+                              Column::Old,
+                              Column::New,
+                              Column::Diff,
+                              Column::DiffRatio,
+                              Column::Speedup];
+
+    fn title(self, ctx: &Context) -> String {
+        match self {
+            Column::Name => "name".to_string(),
+            Column::Old => format!("{} {}", ctx.name_old, ctx.unit),
+            Column::New => format!("{} {}", ctx.name_new, ctx.unit),
+            Column::Diff => format!("diff {}", ctx.unit),
+            Column::DiffRatio => "diff %".to_string(),
+            Column::Speedup => "speedup".to_string(),
+        }
+    }
+
+    fn compare(self, a: &Comparison, b: &Comparison) -> Ordering {
+        match self {
+            Column::Name => a.old.name.cmp(&b.old.name),
+            Column::Old => a.old_value().total_cmp(&b.old_value()),
+            Column::New => a.new_value().total_cmp(&b.new_value()),
+            Column::Diff => a.diff.total_cmp(&b.diff),
+            Column::DiffRatio => a.diff_ratio.total_cmp(&b.diff_ratio),
+            Column::Speedup => a.speedup.total_cmp(&b.speedup),
+        }
+    }
+}
+
+/// The state of the UI.
+struct App<'a> {
+    ctx: &'a Context,
+    /// All comparisons that can be shown.
+    comparisons: Vec<&'a Comparison>,
+    /// The comparisons that match the filter, in the sort order.
+    rows: Vec<&'a Comparison>,
+    /// Only show benchmarks whose name contains this, ignoring case.
+    filter: String,
+    /// Whether keys edit the filter.
+    editing: bool,
+    sort: Column,
+    descending: bool,
+    /// Whether the variance columns are shown.
+    variance: bool,
+    /// Whether the throughput columns are shown.
+    throughput: bool,
+    table: TableState,
+    /// The number of rows that fit in the table, to move by a page.
+    page: usize,
+}
+
+impl<'a> App<'a> {
+    fn new(benches: &'a PairedBenchmarks, ctx: &'a Context) -> App<'a> {
+        let comparisons = ctx.shown(benches);
+        // Only show the throughput columns if there's a throughput besides
+        // the compared metric.
+        let throughput = comparisons.iter()
+            .any(|c| c.metric != "throughput" && c.old.metric("throughput").is_some());
+        let mut app = App {
+            ctx,
+            comparisons,
+            rows: vec![],
+            filter: String::new(),
+            editing: false,
+            sort: Column::Name,
+            descending: false,
+            variance: ctx.variance,
+            throughput,
+            table: TableState::default(),
+            page: 1,
+        };
+        app.update();
+        app
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Recomputes the rows after the filter or the sort order changed,
+    /// keeping the selected benchmark selected if it's still shown.
+    fn update(&mut self) {
+        let selected = self.selected().map(|c| &c.old.name);
+        let filter = self.filter.to_lowercase();
+        let mut rows: Vec<&Comparison> = self.comparisons
+            .iter()
+            .cloned()
+            .filter(|c| c.old.name.to_lowercase().contains(&filter))
+            .collect();
+        let (sort, descending) = (self.sort, self.descending);
+        rows.sort_by(|a, b| {
+            let order = sort.compare(a, b);
+            if descending { order.reverse() } else { order }
+        });
+        let index = selected.and_then(|name| rows.iter().position(|c| &c.old.name == name));
+        self.table.select(index.or(if rows.is_empty() { None } else { Some(0) }));
+        self.rows = rows;
+    }
+
+    fn selected(&self) -> Option<&'a Comparison> {
+        self.table.selected().and_then(|i| self.rows.get(i).cloned())
+    }
+
+    /// Moves the selection by `delta` rows, stopping at the first and last.
+    fn move_by(&mut self, delta: isize) {
+        if let Some(i) = self.table.selected() {
+            let last = self.rows.len() as isize - 1;
+            self.table.select(Some((i as isize + delta).clamp(0, last) as usize));
+        }
+    }
+
+    /// Sorts by `column`, or reverses the order if it's already sorted by it.
+    fn sort_by(&mut self, column: Column) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            self.descending = false;
+        }
+        self.update();
+    }
+
+    /// Handles a key press. Returns false if the UI should quit.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        if self.editing {
+            match key.code {
+                KeyCode::Enter => self.editing = false,
+                KeyCode::Esc => {
+                    self.editing = false;
+                    self.filter.clear();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => return true,
+            }
+            self.update();
+            return true;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => self.editing = true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(self.page as isize),
+            KeyCode::PageUp => self.move_by(-(self.page as isize)),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(-(self.rows.len() as isize)),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(self.rows.len() as isize),
+            KeyCode::Char(c @ '1'..='6') => self.sort_by(Column::ALL[c as usize - '1' as usize]),
+            KeyCode::Char('r') => self.sort_by(self.sort),
+            KeyCode::Char('v') => self.variance = !self.variance,
+            KeyCode::Char('t') => self.throughput = !self.throughput,
+            _ => {}
+        }
+        true
+    }
+
+    /// Returns the titles of the shown columns, marking the one the rows
+    /// are sorted by.
+    fn titles(&self) -> Vec<String> {
+        let mut titles = vec![];
+        for &column in &Column::ALL {
+            let mut title = column.title(self.ctx);
+            if column == self.sort {
+                title.push_str(if self.descending { " ▼" } else { " ▲" });
+            }
+            titles.push(title);
+            let name = match column {
+                Column::Old => &self.ctx.name_old,
+                Column::New => &self.ctx.name_new,
+                _ => continue,
+            };
+            if self.variance {
+                titles.push(format!("{} +/-", name));
+            }
+            if self.throughput {
+                titles.push(format!("{} throughput", name));
+            }
+        }
+        titles
+    }
+
+    /// Returns the cells of the shown columns for a comparison.
+    fn cells(&self, c: &Comparison) -> Vec<String> {
+        let mut cells = vec![c.old.name.clone()];
+        for b in &[&c.old, &c.new] {
+            let metric = &b.metrics[&c.metric];
+            cells.push(metric.fmt(false));
+            if self.variance {
+                cells.push(metric.fmt_variance());
+            }
+            if self.throughput {
+                cells.push(b.metric("throughput")
+                               .map_or("n/a".to_string(), |t| format!("{} {}", t.fmt(false), t.unit)));
+            }
+        }
+        cells.push(c.fmt_diff());
+        cells.push(format!("{:.2}%", c.diff_ratio * 100f64));
+        cells.push(format!("x {:.2}", c.speedup));
+        cells
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, detail_area, status_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(DETAIL_HEIGHT), Constraint::Length(1)])
+                .areas(frame.area());
+
+        let titles = self.titles();
+        let rows: Vec<Vec<String>> = self.rows.iter().map(|c| self.cells(c)).collect();
+        let widths: Vec<Constraint> = titles.iter()
+            .enumerate()
+            .map(|(i, title)| {
+                let title = title.chars().count();
+                let width = rows.iter().map(|row| row[i].chars().count()).fold(title, usize::max);
+                Constraint::Length(width as u16)
+            })
+            .collect();
+        let header = Row::new(titles.into_iter().map(Cell::from))
+            .style(Style::new().add_modifier(Modifier::BOLD));
+        let table = Table::new(self.rows.iter().zip(rows).map(|(c, row)| {
+                    let color = if c.is_regression() { Color::Red } else { Color::Green };
+                    Row::new(row).style(Style::new().fg(color))
+                }),
+                widths)
+            .header(header)
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .block(Block::new().borders(Borders::ALL));
+        self.page = table_area.height.saturating_sub(3).max(1) as usize;
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let detail = Paragraph::new(self.detail()).block(Block::new().borders(Borders::ALL));
+        frame.render_widget(detail, detail_area);
+
+        let status = if self.editing {
+            format!("/{}", self.filter)
+        } else {
+            let mut status = format!("{} of {} benchmarks", self.rows.len(), self.comparisons.len());
+            if !self.filter.is_empty() {
+                status.push_str(&format!(" matching {:?}", self.filter));
+            }
+            format!("{}  |  {}", status, KEYS)
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+    }
+
+    /// Formats the metric named `metric` of `b` with its variance and unit,
+    /// followed by the throughput if the metric is the time.
+    fn measurement(&self, b: &Benchmark, metric: &str) -> String {
+        let mut res = format!("{} {}", b.metrics[metric].fmt(true), self.ctx.unit);
+        if let (true, Some(throughput)) = (metric == "time", b.metric("throughput")) {
+            res = format!("{} ({} {})", res, throughput.fmt(false), throughput.unit);
+        }
+        res
+    }
+
+    /// Returns the lines of the detail pane for the selected benchmark.
+    fn detail(&self) -> Vec<Line<'static>> {
+        let c = match self.selected() {
+            Some(c) => c,
+            None => return vec![Line::from("no benchmark selected")],
+        };
+        let mut origin = vec![];
+        if let Some(ref krate) = c.old.krate {
+            origin.push(format!("crate {}", krate));
+        }
+        if let Some(ref target) = c.old.target {
+            origin.push(format!("target {}", target));
+        }
+        let mut lines = vec![
+            Line::styled(if origin.is_empty() {
+                             c.old.name.clone()
+                         } else {
+                             format!("{} ({})", c.old.name, origin.join(", "))
+                         },
+                         Style::new().add_modifier(Modifier::BOLD)),
+            Line::from(format!("{}: {}", self.ctx.name_old, self.measurement(&c.old, &c.metric))),
+            Line::from(format!("{}: {}", self.ctx.name_new, self.measurement(&c.new, &c.metric))),
+            Line::from(format!("diff: {} {} ({:.2}%), speedup x {:.2}",
+                               c.fmt_diff(),
+                               self.ctx.unit,
+                               c.diff_ratio * 100f64,
+                               c.speedup)),
+        ];
+        for name in c.old.metric_names().into_iter().filter(|&name| name != c.metric) {
+            if let Some(m) = c.metric(name) {
+                let percent = m.diff_ratio.map_or("n/a".to_string(), |r| format!("{:+.2}%", r * 100f64));
+                lines.push(Line::from(format!("{}: {} -> {} {} ({})",
+                                              name,
+                                              m.old.fmt(false),
+                                              m.new.fmt(false),
+                                              m.old.unit,
+                                              percent)));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use ratatui::Terminal;
+
+    use benchcmp::{self, Benchmarks, Context, PairedBenchmarks};
+    use super::App;
+
+    fn paired() -> PairedBenchmarks {
+        let old = "test parse_json ... bench:       1,000 ns/iter (+/- 10)\n\
+                   test parse_csv  ... bench:       2,000 ns/iter (+/- 20)\n\
+                   test render     ... bench:         500 ns/iter (+/- 5)";
+        let new = "test parse_json ... bench:         800 ns/iter (+/- 10)\n\
+                   test parse_csv  ... bench:       2,600 ns/iter (+/- 20)\n\
+                   test render     ... bench:         490 ns/iter (+/- 5)";
+        let old = benchcmp::parse_buffer(old.as_bytes()).unwrap();
+        let new = benchcmp::parse_buffer(new.as_bytes()).unwrap();
+        Benchmarks::from((old, new)).paired()
+    }
+
+    fn names(app: &App) -> Vec<String> {
+        app.rows.iter().map(|c| c.old.name.clone()).collect()
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            app.key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn filter_and_sort() {
+        let paired = paired();
+        let ctx = Context::new("old", "new");
+        let mut app = App::new(&paired, &ctx);
+        assert_eq!(names(&app), ["parse_csv", "parse_json", "render"]);
+
+        press(&mut app, "5");
+        assert_eq!(names(&app), ["parse_json", "render", "parse_csv"]);
+        assert_eq!(app.selected().unwrap().old.name, "parse_csv");
+        press(&mut app, "k5");
+        assert_eq!(names(&app), ["parse_csv", "render", "parse_json"]);
+        assert_eq!(app.selected().unwrap().old.name, "render");
+
+        press(&mut app, "/PARSE");
+        assert!(app.editing);
+        app.key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(names(&app), ["parse_csv", "parse_json"]);
+        assert_eq!(app.selected().unwrap().old.name, "parse_csv");
+        assert!(!app.key(KeyEvent::from(KeyCode::Char('q'))));
+    }
+
+    #[test]
+    fn draw() {
+        let paired = paired();
+        let ctx = Context::new("old", "new");
+        let mut app = App::new(&paired, &ctx);
+        press(&mut app, "vj");
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let text: String = terminal.backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(text.contains("new +/-"));
+        assert!(!text.contains("new throughput"));
+        assert!(text.contains("diff: -200 ns/iter (-20.00%), speedup x 1.25"));
+        assert!(text.contains("3 of 3 benchmarks"));
+    }
+
+    #[test]
+    fn toggle_columns() {
+        let old = "test parse ... bench:       1,000 ns/iter (+/- 10) = 300 MB/s";
+        let new = "test parse ... bench:         800 ns/iter (+/- 8) = 375 MB/s";
+        let old = benchcmp::parse_buffer(old.as_bytes()).unwrap();
+        let new = benchcmp::parse_buffer(new.as_bytes()).unwrap();
+        let paired = Benchmarks::from((old, new)).paired();
+        let ctx = Context::new("old", "new");
+        let mut app = App::new(&paired, &ctx);
+        let c = app.rows[0];
+        assert_eq!(app.cells(c), ["parse", "1,000", "300 MB/s", "800", "375 MB/s", "-200", "-20.00%", "x 1.25"]);
+
+        press(&mut app, "vt");
+        assert_eq!(app.titles()[..5], ["name ▲", "old ns/iter", "old +/-", "new ns/iter", "new +/-"]);
+        assert_eq!(app.cells(c)[..5], ["parse", "1,000", "10", "800", "8"]);
+    }
+
+    #[test]
+    fn detail_with_throughput() {
+        let old = "test parse ... bench:       1,000 ns/iter (+/- 10) = 300 MB/s";
+        let new = "test parse ... bench:         800 ns/iter (+/- 8) = 375 MB/s";
+        let old = benchcmp::parse_buffer(old.as_bytes()).unwrap();
+        let new = benchcmp::parse_buffer(new.as_bytes()).unwrap();
+        let paired = Benchmarks::from((old, new)).paired();
+        let ctx = Context::new("old", "new");
+        let app = App::new(&paired, &ctx);
+        let lines: Vec<String> = app.detail().iter().map(|l| l.to_string()).collect();
+        assert_eq!(lines[1], "old: 1,000 (+/- 10) ns/iter (300 MB/s)");
+        assert_eq!(lines[2], "new: 800 (+/- 8) ns/iter (375 MB/s)");
+    }
+}
@@ -63,6 +63,11 @@ impl CommandUnderTest {
         self
     }
 
+    fn env<K: AsRef<OsStr>, V: AsRef<OsStr>>(&mut self, key: K, val: V) -> &mut Self {
+        self.raw.env(key, val);
+        self
+    }
+
     fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Self {
         self.raw.arg(arg);
         self
@@ -76,8 +81,8 @@ impl CommandUnderTest {
         self
     }
 
-    fn pipe_in(&mut self, fixture: &str) -> &mut Self {
-        self.stdin = Vec::from(fixture);
+    fn pipe_in<B: AsRef<[u8]>>(&mut self, fixture: B) -> &mut Self {
+        self.stdin = Vec::from(fixture.as_ref());
         self.raw.stdin(Stdio::piped());
         self
     }
@@ -85,7 +90,7 @@ impl CommandUnderTest {
     fn run(&mut self) -> ExitStatus {
         let mut child = self.raw.spawn().expect("failed to run command");
 
-        if self.stdin.len() > 0 {
+        if !self.stdin.is_empty() {
             let stdin = child.stdin.as_mut().expect("failed to open stdin");
             stdin.write_all(&self.stdin).expect("failed to write to stdin")
         }
@@ -104,24 +109,25 @@ impl CommandUnderTest {
 
     fn succeeds(&mut self) -> &mut Self {
         let status = self.run();
-        assert!(status.success(), format!(
+        assert!(
+            status.success(),
             "expected command to succeed, but it failed.\nexit code: {}\nstdout: {}\nstderr:{}\n",
             status.code().unwrap(),
             self.stdout,
             self.stderr,
-        ));
+        );
         self
     }
 
     fn no_stdout(&mut self) -> &mut Self {
         assert!(self.run, "command has not yet been run, use succeeds()/fails()");
-        assert!(self.stdout.is_empty(), format!("expected no stdout, got {}", self.stdout));
+        assert!(self.stdout.is_empty(), "expected no stdout, got {}", self.stdout);
         self
     }
 
     fn no_stderr(&mut self) -> &mut Self {
         assert!(self.run, "command has not yet been run, use succeeds()/fails()");
-        assert!(self.stderr.is_empty(), format!("expected no stderr, got {}", self.stderr));
+        assert!(self.stderr.is_empty(), "expected no stderr, got {}", self.stderr);
         self
     }
 
@@ -158,7 +164,7 @@ fn version() {
 #[test]
 fn same_input() {
     new_cmd()
-        .args(&["bench_output_1.txt", "bench_output_1.txt"])
+        .args(["bench_output_1.txt", "bench_output_1.txt"])
         .succeeds()
         .stdout_is(include_str!("fixtures/same_input.expected"));
 }
@@ -166,7 +172,7 @@ fn same_input() {
 #[test]
 fn same_input_fractional() {
     new_cmd()
-        .args(&["bench_output_fraction.txt", "bench_output_fraction.txt"])
+        .args(["bench_output_fraction.txt", "bench_output_fraction.txt"])
         .succeeds()
         .stdout_is(include_str!("fixtures/same_input_fractional.expected"));
 }
@@ -174,7 +180,7 @@ fn same_input_fractional() {
 #[test]
 fn different_input() {
     new_cmd()
-        .args(&["bench_output_2.txt", "bench_output_3.txt"])
+        .args(["bench_output_2.txt", "bench_output_3.txt"])
         .succeeds()
         .no_stderr()
         .stdout_is(include_str!("fixtures/different_input.expected"));
@@ -183,7 +189,7 @@ fn different_input() {
 #[test]
 fn non_overlapping_input() {
     new_cmd()
-        .args(&["bench_output_1.txt", "bench_output_2.txt"])
+        .args(["bench_output_1.txt", "bench_output_2.txt"])
         .succeeds()
         .stderr_is(include_str!("fixtures/non_overlapping_input.expected"))
         .no_stdout();
@@ -196,7 +202,7 @@ fn different_input_colored() {
         // NOTE: keeping the environment here so that terminfo is available,
         //  which is required to get colour code in the output
         .keep_env()
-        .args(&["--color", "always", "bench_output_2.txt", "bench_output_3.txt"])
+        .args(["--color", "always", "bench_output_2.txt", "bench_output_3.txt"])
         .succeeds()
         .no_stderr()
         .stdout_is(include_str!("fixtures/different_input_colored.expected"));
@@ -205,7 +211,7 @@ fn different_input_colored() {
 #[test]
 fn different_input_selections() {
     new_cmd()
-        .args(&["dense::", "dense_boxed::", "bench_output_1.txt"])
+        .args(["dense::", "dense_boxed::", "bench_output_1.txt"])
         .succeeds()
         .no_stderr()
         .stdout_is(include_str!("fixtures/different_input_selections.expected"));
@@ -214,7 +220,7 @@ fn different_input_selections() {
 #[test]
 fn stdin() {
     new_cmd()
-        .args(&["dense::", "dense_boxed::", "-"])
+        .args(["dense::", "dense_boxed::", "-"])
         .pipe_in(include_str!("fixtures/bench_output_1.txt"))
         .succeeds()
         .no_stderr()
@@ -224,7 +230,7 @@ fn stdin() {
 #[test]
 fn empty_results() {
     new_cmd()
-        .args(&["bench_output_4.txt", "bench_output_5.txt", "--regressions", "--improvements"])
+        .args(["bench_output_4.txt", "bench_output_5.txt", "--regressions", "--improvements"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/empty_results.expected"));
@@ -233,7 +239,7 @@ fn empty_results() {
 #[test]
 fn within_threshold_1_comparing_4_5() {
     new_cmd()
-        .args(&["bench_output_4.txt", "bench_output_5.txt", "--threshold", "1"])
+        .args(["bench_output_4.txt", "bench_output_5.txt", "--threshold", "1"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/4_cmp_5_within_threshold.expected"));
@@ -242,7 +248,7 @@ fn within_threshold_1_comparing_4_5() {
 #[test]
 fn within_threshold_12_comparing_6_7() {
     new_cmd()
-        .args(&["bench_output_6.txt", "bench_output_7.txt", "--threshold", "12"])
+        .args(["bench_output_6.txt", "bench_output_7.txt", "--threshold", "12"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/6_cmp_7_within_threshold.expected"));
@@ -251,7 +257,7 @@ fn within_threshold_12_comparing_6_7() {
 #[test]
 fn within_threshold_3_comparing_6_7_improvements() {
     new_cmd()
-        .args(&["bench_output_6.txt", "bench_output_7.txt", "--threshold", "3", "--improvements"])
+        .args(["bench_output_6.txt", "bench_output_7.txt", "--threshold", "3", "--improvements"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/6_cmp_7_within_threshold_improvements.expected"));
@@ -260,7 +266,7 @@ fn within_threshold_3_comparing_6_7_improvements() {
 #[test]
 fn within_threshold_4_comparing_6_7_regressions() {
     new_cmd()
-        .args(&["bench_output_6.txt", "bench_output_7.txt", "--threshold", "4", "--regressions"])
+        .args(["bench_output_6.txt", "bench_output_7.txt", "--threshold", "4", "--regressions"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/6_cmp_7_within_threshold_regressions.expected"));
@@ -269,7 +275,7 @@ fn within_threshold_4_comparing_6_7_regressions() {
 #[test]
 fn zero_regressions() {
     new_cmd()
-        .args(&["bench_output_4.txt", "bench_output_5.txt", "--regressions"])
+        .args(["bench_output_4.txt", "bench_output_5.txt", "--regressions"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/zero_regressions.expected"));
@@ -278,7 +284,7 @@ fn zero_regressions() {
 #[test]
 fn zero_regressions_threshold() {
     new_cmd()
-        .args(&["bench_output_4.txt", "bench_output_5.txt", "--threshold", "2", "--regressions"])
+        .args(["bench_output_4.txt", "bench_output_5.txt", "--threshold", "2", "--regressions"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/zero_regressions.expected"));
@@ -287,7 +293,7 @@ fn zero_regressions_threshold() {
 #[test]
 fn zero_improvements() {
     new_cmd()
-        .args(&["bench_output_4.txt", "bench_output_8.txt", "--improvements"])
+        .args(["bench_output_4.txt", "bench_output_8.txt", "--improvements"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/zero_improvements.expected"));
@@ -296,8 +302,452 @@ fn zero_improvements() {
 #[test]
 fn zero_improvements_threshold() {
     new_cmd()
-        .args(&["bench_output_4.txt", "bench_output_8.txt", "--threshold", "2", "--improvements"])
+        .args(["bench_output_4.txt", "bench_output_8.txt", "--threshold", "2", "--improvements"])
         .succeeds()
         .no_stdout()
         .stderr_is(include_str!("fixtures/zero_improvements.expected"));
 }
+
+#[test]
+fn normalize_by() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_3.txt", "--normalize-by", "ac_one_byte"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/normalize_by.expected"));
+}
+
+#[test]
+fn normalize_by_missing_reference() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_3.txt", "--normalize-by", "nope"])
+        .fails()
+        .no_stdout()
+        .stderr_is(include_str!("fixtures/normalize_by_missing.expected"));
+}
+
+#[test]
+fn different_input_html() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_3.txt", "--format", "html", "--threshold", "20"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/different_input_html.expected"));
+}
+
+#[test]
+fn svg() {
+    new_cmd()
+        .args(["bench_output_6.txt", "bench_output_7.txt", "--format", "svg"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/svg.expected"));
+}
+
+#[test]
+fn svg_zero() {
+    new_cmd()
+        .args(["bench_output_zero_1.txt", "bench_output_zero_2.txt", "--format", "svg"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/svg_zero.expected"));
+}
+
+#[test]
+fn bars_unicode() {
+    new_cmd()
+        .env("LC_ALL", "en_US.UTF-8")
+        .args(["bench_output_6.txt", "bench_output_7.txt", "--bars"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/bars_unicode.expected"));
+}
+
+#[test]
+fn bars_ascii() {
+    new_cmd()
+        .env("LC_ALL", "C")
+        .args(["bench_output_6.txt", "bench_output_7.txt", "--bars"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/bars_ascii.expected"));
+}
+
+#[test]
+fn bars_include_missing() {
+    new_cmd()
+        .env("LC_ALL", "C")
+        .args(["bench_output_6.txt", "bench_output_8.txt", "--bars", "--include-missing"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/bars_include_missing.expected"));
+}
+
+#[test]
+fn junit() {
+    new_cmd()
+        .args(["bench_output_4.txt", "bench_output_7.txt", "--format", "junit"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/junit.expected"));
+}
+
+#[test]
+fn github() {
+    let summary = env::temp_dir().join(format!("benchcmp-github-{}.md", std::process::id()));
+    new_cmd()
+        .env("GITHUB_STEP_SUMMARY", &summary)
+        .args(["bench_output_2.txt", "bench_output_3.txt", "--format", "github", "--threshold", "25"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/github.expected"));
+    let written = std::fs::read_to_string(&summary).unwrap();
+    std::fs::remove_file(&summary).unwrap();
+    assert_eq!(written, include_str!("fixtures/github_summary.expected"));
+}
+
+#[test]
+fn tap() {
+    new_cmd()
+        .args(["bench_output_4.txt", "bench_output_7.txt", "--format", "tap", "--threshold", "0"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/tap.expected"));
+}
+
+#[test]
+fn different_input_json() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_3.json"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/different_input_json.expected"));
+}
+
+#[test]
+fn unknown_input_format() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_3.json", "--input-format", "xml"])
+        .fails()
+        .no_stdout()
+        .stderr_is("unknown input format xml (expected auto or one of: hyperfine, libtest-json, divan, iai, libtest)\n");
+}
+
+#[test]
+fn criterion_baselines() {
+    new_cmd()
+        .args(["main", "new", "criterion_a", "--include-missing"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/criterion_baselines.expected"));
+}
+
+#[test]
+fn criterion_dirs() {
+    new_cmd()
+        .args(["criterion_a", "criterion_b", "--variance"])
+        .succeeds()
+        .stdout_is(include_str!("fixtures/criterion_dirs.expected"))
+        .stderr_is("WARNING: benchmarks in new but not in old: parse/json\n");
+}
+
+#[test]
+fn iai_metric() {
+    new_cmd()
+        .args(["iai_1.txt", "iai_2.txt", "--metric", "instructions"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/iai_instructions.expected"));
+}
+
+#[test]
+fn iai_default_metric() {
+    new_cmd()
+        .args(["iai_1.txt", "iai_2.txt"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/iai_estimated_cycles.expected"));
+}
+
+#[test]
+fn missing_metric() {
+    new_cmd()
+        .args(["iai_1.txt", "iai_2.txt", "--metric", "nope"])
+        .fails()
+        .no_stdout()
+        .stderr_is("benchmark bench_fibonacci_short has no nope metric\n");
+}
+
+#[test]
+fn metric_missing_on_one_side() {
+    new_cmd()
+        .args(["iai_1.txt", "bench_output_fibonacci.txt"])
+        .succeeds()
+        .stderr_is("WARNING: benchmarks in new but not in old: bench_sum\n\
+                    WARNING: benchmarks in both old and new, but without a time metric in one: \
+                    bench_fibonacci_long, bench_fibonacci_short\n");
+}
+
+#[test]
+fn criterion_metrics() {
+    new_cmd()
+        .args(["main", "new", "criterion_a", "--metrics", "mean,throughput,elements"])
+        .succeeds()
+        .stdout_is(include_str!("fixtures/criterion_metrics.expected"))
+        .stderr_is("WARNING: benchmarks in old but not in new: parse/json\n");
+}
+
+#[test]
+fn higher_is_better_metric() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_3.txt", "--metric", "throughput", "--threshold", "20"])
+        .succeeds()
+        .stdout_is(include_str!("fixtures/throughput_metric.expected"));
+}
+
+#[test]
+fn normalize_by_metrics() {
+    new_cmd()
+        .args(["main", "new", "criterion_a", "--normalize-by", "sum", "--metrics", "mean,time"])
+        .succeeds()
+        .stdout_is(include_str!("fixtures/normalize_by_metrics.expected"));
+}
+
+#[test]
+fn malformed_input_warns() {
+    new_cmd()
+        .args(["bench_output_malformed.txt", "bench_output_malformed.txt"])
+        .succeeds()
+        .no_stdout()
+        .stderr_is("WARNING: no benchmarks found in bench_output_malformed.txt, but 2 malformed \
+                    results were skipped (first at line 3: expected a time like `1,234 ns/iter` \
+                    after `bench:`)\n\
+                    WARNING: no benchmarks found in bench_output_malformed.txt, but 2 malformed \
+                    results were skipped (first at line 3: expected a time like `1,234 ns/iter` \
+                    after `bench:`)\n");
+}
+
+#[test]
+fn malformed_input_strict() {
+    new_cmd()
+        .args(["bench_output_malformed.txt", "bench_output_2.txt", "--strict"])
+        .fails()
+        .no_stdout()
+        .stderr_is("bench_output_malformed.txt: line 3: expected a time like `1,234 ns/iter` \
+                    after `bench:`\n");
+}
+
+#[test]
+fn failed_benchmarks() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_failed.txt"])
+        .succeeds()
+        .stdout_is(include_str!("fixtures/failed_benchmarks.expected"))
+        .stderr_is("WARNING: ac_one_byte failed in new: assertion failed: haystack.len() > 0\n\
+                    WARNING: ac_two_bytes was ignored in new\n");
+}
+
+#[test]
+fn failed_benchmarks_junit() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_failed.txt", "--format", "junit"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/failed_benchmarks_junit.expected"));
+}
+
+#[test]
+fn tap_multiline_panic() {
+    new_cmd()
+        .args(["bench_output_failed_multiline.txt", "bench_output_failed_multiline.txt", "--format", "tap"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/tap_multiline_panic.expected"));
+}
+
+#[test]
+fn duplicates_error() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_duplicates.txt", "--duplicates", "error"])
+        .fails()
+        .no_stdout()
+        .stderr_is("benchmark ac_one_byte appears 2 times in new benchmarks\n");
+}
+
+#[test]
+fn duplicates_target() {
+    new_cmd()
+        .args(["bench_output_duplicates.txt", "bench_output_duplicates.txt", "--duplicates", "target"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/duplicates_target.expected"));
+}
+
+#[test]
+fn workspace_duplicates_warn() {
+    new_cmd()
+        .args(["bench_output_workspace_1.txt", "bench_output_workspace_2.txt"])
+        .succeeds()
+        .stdout_is(include_str!("fixtures/workspace_duplicates_warn.expected"))
+        .stderr_is("WARNING: benchmark literal appears 2 times in old benchmarks, keeping the first\n\
+                    WARNING: benchmark unicode_class appears 2 times in old benchmarks, keeping the first\n\
+                    WARNING: benchmark literal appears 2 times in new benchmarks, keeping the first\n\
+                    WARNING: benchmark unicode_class appears 2 times in new benchmarks, keeping the first\n");
+}
+
+#[test]
+fn workspace_duplicates_target() {
+    new_cmd()
+        .args(["bench_output_workspace_1.txt", "bench_output_workspace_2.txt", "--duplicates", "target"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/workspace_duplicates_target.expected"));
+}
+
+#[test]
+fn workspace_qualify_crate() {
+    new_cmd()
+        .args(["bench_output_workspace_1.txt", "bench_output_workspace_2.txt", "--qualify", "crate"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/workspace_qualify_crate.expected"));
+}
+
+#[test]
+fn workspace_crate() {
+    new_cmd()
+        .args(["bench_output_workspace_1.txt", "bench_output_workspace_2.txt", "--crate", "regex_syntax"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/workspace_crate.expected"));
+}
+
+#[test]
+fn ci_log() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_ci_log.txt"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/ci_log.expected"));
+}
+
+#[test]
+fn compressed() {
+    new_cmd()
+        .args(["bench_output_2.txt", "bench_output_2.txt.zst"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/compressed.expected"));
+}
+
+#[test]
+fn compressed_selections() {
+    new_cmd()
+        .args(["dense::", "dense_boxed::", "bench_output_1.txt.gz"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/different_input_selections.expected"));
+}
+
+#[test]
+fn compressed_stdin() {
+    new_cmd()
+        .args(["dense::", "dense_boxed::", "-"])
+        .pipe_in(&include_bytes!("fixtures/bench_output_1.txt.gz")[..])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/different_input_selections.expected"));
+}
+
+#[test]
+fn prefixes() {
+    new_cmd()
+        .args(["--prefixes", "v1::,v2::,v3::", "bench_output_versions.txt"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/prefixes.expected"));
+}
+
+#[test]
+fn prefixes_two_files() {
+    new_cmd()
+        .args(["--prefixes", "v1::,v2::,v3::", "bench_output_versions.txt", "bench_output_versions.txt"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/prefixes.expected"));
+}
+
+#[test]
+fn prefixes_format() {
+    new_cmd()
+        .args(["--prefixes", "v1::,v2::,v3::", "--format", "junit", "bench_output_versions.txt"])
+        .fails()
+        .no_stdout()
+        .stderr_is("only the table format can compare more than two prefixes\n");
+}
+
+#[test]
+fn selections_two_files() {
+    new_cmd()
+        .args(["dense::", "dense_boxed::", "bench_output_1.txt", "bench_output_1.txt"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/different_input_selections.expected"));
+}
+
+#[test]
+fn split() {
+    new_cmd()
+        .args(["--split", r"/(?P<variant>\w+)$", "scalar", "simd", "bench_output_variants.txt"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/split.expected"));
+}
+
+#[test]
+fn split_prefixes() {
+    new_cmd()
+        .args(["--split", r"/(?P<variant>\w+)$", "--prefixes", "scalar,simd,avx512", "--include-missing",
+               "bench_output_variants.txt"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/split_prefixes.expected"));
+}
+
+#[test]
+fn split_without_variant() {
+    new_cmd()
+        .args(["--split", r"/(\w+)$", "scalar", "simd", "bench_output_variants.txt"])
+        .fails()
+        .no_stdout()
+        .stderr_is("the regex of --split has no capture group named variant\n");
+}
+
+#[test]
+fn scaling() {
+    new_cmd()
+        .args(["--scaling", r"_(?P<param>\d+)$", "bench_output_scaling_1.txt", "bench_output_scaling_2.txt"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is(include_str!("fixtures/scaling.expected"));
+}
+
+#[test]
+fn scaling_format() {
+    new_cmd()
+        .args(["--scaling", r"_(?P<param>\d+)$", "--format", "tap", "bench_output_scaling_1.txt",
+               "bench_output_scaling_2.txt"])
+        .fails()
+        .no_stdout()
+        .stderr_is("--scaling only supports two sets of benchmarks and the table format\n");
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn tui_needs_terminal() {
+    new_cmd()
+        .args(["--tui", "bench_output_1.txt", "bench_output_2.txt"])
+        .fails()
+        .no_stdout()
+        .stderr_is("--tui needs a terminal\n");
+}